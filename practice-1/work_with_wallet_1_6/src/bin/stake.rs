@@ -0,0 +1,282 @@
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::{LAMPORTS_PER_SOL, lamports_to_sol};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use solana_stake_interface::instruction as stake_instruction;
+use solana_stake_interface::program as stake_program;
+use solana_stake_interface::state::{Authorized, Lockup, StakeStateV2};
+use std::process::exit;
+use std::str::FromStr;
+use work_with_wallet_1_6::load_keypair;
+use work_with_wallet_1_6::stake::get_stake_account;
+
+const USAGE: &str = "Usage: stake <command>
+
+Commands:
+  create <amount_sol> [--seed=<seed>]
+  delegate <stake_account> <vote_account>
+  deactivate <stake_account>
+  withdraw <stake_account> <amount_sol|all> [--to=<recipient>]
+  split <stake_account> <amount_sol> [--seed=<seed>]
+  merge <destination_stake_account> <source_stake_account>
+
+Our wallet is the stake and withdraw authority of every account it creates.
+With --seed the new account is derived from our wallet and the seed, so it
+can be found again without keeping a keypair.  Use `stakes` to list them.";
+
+pub fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (positional, options): (Vec<&str>, Vec<&str>) = args
+        .iter()
+        .map(String::as_str)
+        .partition(|arg| !arg.starts_with("--"));
+    let option = |name: &str| {
+        options
+            .iter()
+            .find_map(|option| option.strip_prefix(&format!("--{}=", name)))
+    };
+    let arg = |index: usize| *positional.get(index).unwrap_or_else(|| fail(USAGE));
+
+    let rpc_url = String::from("https://api.devnet.solana.com");
+    let connection = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+    let wallet = load_keypair();
+
+    match arg(0) {
+        "create" => create(&connection, &wallet, parse_sol(arg(1)), option("seed")),
+        "delegate" => {
+            let stake = parse_pubkey(arg(1));
+            let vote = parse_pubkey(arg(2));
+            let instruction = stake_instruction::delegate_stake(&stake, &wallet.pubkey(), &vote);
+            send(&connection, &[instruction], &wallet, &[]);
+            println!("Stake account {} delegated to {}", stake, vote);
+        }
+        "deactivate" => {
+            let stake = parse_pubkey(arg(1));
+            let instruction = stake_instruction::deactivate_stake(&stake, &wallet.pubkey());
+            send(&connection, &[instruction], &wallet, &[]);
+            println!(
+                "Stake account {} deactivates at the end of the epoch",
+                stake
+            );
+        }
+        "withdraw" => {
+            let stake = parse_pubkey(arg(1));
+            let to = option("to").map_or(wallet.pubkey(), parse_pubkey);
+            let lamports = match arg(2) {
+                "all" => fetch_stake_account_lamports(&connection, &stake),
+                amount => parse_sol(amount),
+            };
+            let instruction =
+                stake_instruction::withdraw(&stake, &wallet.pubkey(), &to, lamports, None);
+            send(&connection, &[instruction], &wallet, &[]);
+            println!("Withdrew {} SOL to {}", lamports_to_sol(lamports), to);
+        }
+        "split" => split(
+            &connection,
+            &wallet,
+            parse_pubkey(arg(1)),
+            parse_sol(arg(2)),
+            option("seed"),
+        ),
+        "merge" => {
+            let destination = parse_pubkey(arg(1));
+            let source = parse_pubkey(arg(2));
+            let instructions = stake_instruction::merge(&destination, &source, &wallet.pubkey());
+            send(&connection, &instructions, &wallet, &[]);
+            println!("Merged {} into {}", source, destination);
+        }
+        _ => fail(USAGE),
+    }
+}
+
+/// Creates a stake account holding `lamports` on top of its rent reserve.
+fn create(connection: &RpcClient, wallet: &impl Signer, lamports: u64, seed: Option<&str>) {
+    let rent = rent_exempt_reserve(connection);
+    let minimum = connection
+        .get_stake_minimum_delegation()
+        .unwrap_or_else(|err| fail(&format!("Failed to get minimum delegation: {}", err)));
+    if lamports < minimum {
+        fail(&format!(
+            "At least {} SOL must be staked",
+            lamports_to_sol(minimum)
+        ));
+    }
+
+    let authorized = Authorized::auto(&wallet.pubkey());
+    let stake_keypair = Keypair::new();
+    let (stake, instructions) = match seed {
+        Some(seed) => {
+            let stake = derive_address(&wallet.pubkey(), seed);
+            let instructions = stake_instruction::create_account_with_seed(
+                &wallet.pubkey(),
+                &stake,
+                &wallet.pubkey(),
+                seed,
+                &authorized,
+                &Lockup::default(),
+                rent + lamports,
+            );
+            (stake, instructions)
+        }
+        None => {
+            let stake = stake_keypair.pubkey();
+            let instructions = stake_instruction::create_account(
+                &wallet.pubkey(),
+                &stake,
+                &authorized,
+                &Lockup::default(),
+                rent + lamports,
+            );
+            (stake, instructions)
+        }
+    };
+
+    let extra_signers: Vec<&dyn Signer> = match seed {
+        Some(_) => vec![],
+        None => vec![&stake_keypair],
+    };
+    send(connection, &instructions, wallet, &extra_signers);
+    println!(
+        "Stake account {} created with {} SOL",
+        stake,
+        lamports_to_sol(lamports)
+    );
+}
+
+/// Moves `lamports` of a stake account into a new one, which keeps the
+/// delegation.
+fn split(
+    connection: &RpcClient,
+    wallet: &impl Signer,
+    stake: Pubkey,
+    lamports: u64,
+    seed: Option<&str>,
+) {
+    let split_keypair = Keypair::new();
+    let (split_stake, mut instructions) = match seed {
+        Some(seed) => {
+            let split_stake = derive_address(&wallet.pubkey(), seed);
+            let instructions = stake_instruction::split_with_seed(
+                &stake,
+                &wallet.pubkey(),
+                lamports,
+                &split_stake,
+                &wallet.pubkey(),
+                seed,
+            );
+            (split_stake, instructions)
+        }
+        None => {
+            let split_stake = split_keypair.pubkey();
+            let instructions =
+                stake_instruction::split(&stake, &wallet.pubkey(), lamports, &split_stake);
+            (split_stake, instructions)
+        }
+    };
+
+    // The new account must be rent exempt on its own, the split only moves
+    // stake, so the reserve is sent in front of it.
+    let rent = rent_exempt_reserve(connection);
+    instructions.insert(
+        0,
+        solana_sdk::system_instruction::transfer(&wallet.pubkey(), &split_stake, rent),
+    );
+
+    let extra_signers: Vec<&dyn Signer> = match seed {
+        Some(_) => vec![],
+        None => vec![&split_keypair],
+    };
+    send(connection, &instructions, wallet, &extra_signers);
+    println!(
+        "Split {} SOL of {} into {}",
+        lamports_to_sol(lamports),
+        stake,
+        split_stake
+    );
+}
+
+fn derive_address(base: &Pubkey, seed: &str) -> Pubkey {
+    Pubkey::create_with_seed(base, seed, &stake_program::id())
+        .unwrap_or_else(|err| fail(&format!("Invalid seed {}: {}", seed, err)))
+}
+
+fn rent_exempt_reserve(connection: &RpcClient) -> u64 {
+    connection
+        .get_minimum_balance_for_rent_exemption(StakeStateV2::size_of())
+        .unwrap_or_else(|err| fail(&format!("Failed to get rent: {}", err)))
+}
+
+fn fetch_stake_account_lamports(connection: &RpcClient, stake: &Pubkey) -> u64 {
+    get_stake_account(connection, stake)
+        .unwrap_or_else(|err| fail(&format!("Failed to read stake account: {}", err)))
+        .lamports
+}
+
+fn send(
+    connection: &RpcClient,
+    instructions: &[Instruction],
+    wallet: &impl Signer,
+    extra_signers: &[&dyn Signer],
+) {
+    let blockhash = connection
+        .get_latest_blockhash()
+        .unwrap_or_else(|err| fail(&format!("Failed to get recent blockhash: {}", err)));
+    let mut signers: Vec<&dyn Signer> = vec![wallet];
+    signers.extend_from_slice(extra_signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&wallet.pubkey()),
+        &signers,
+        blockhash,
+    );
+
+    match connection.send_and_confirm_transaction(&transaction) {
+        Ok(signature) => println!("Transaction signature: {}", signature),
+        Err(err) => fail(&format!("Transaction failed: {}", err)),
+    }
+}
+
+/// Parses a SOL amount into lamports without going through `f64`, so that
+/// e.g. 0.1 SOL is exactly 100000000 lamports.  More than 9 decimals is an
+/// error rather than being rounded.
+fn parse_sol(amount: &str) -> u64 {
+    let invalid = || fail(&format!("Invalid amount: {}", amount));
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if fraction.len() > 9
+        || (whole.is_empty() && fraction.is_empty())
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        invalid();
+    }
+
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().unwrap_or_else(|_| invalid())
+    };
+    let fraction: u64 = format!("{:0<9}", fraction)
+        .parse()
+        .unwrap_or_else(|_| invalid());
+    match whole
+        .checked_mul(LAMPORTS_PER_SOL)
+        .and_then(|lamports| lamports.checked_add(fraction))
+    {
+        Some(lamports) if lamports > 0 => lamports,
+        _ => invalid(),
+    }
+}
+
+fn parse_pubkey(address: &str) -> Pubkey {
+    Pubkey::from_str(address).unwrap_or_else(|_| fail(&format!("Invalid address: {}", address)))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    exit(1);
+}
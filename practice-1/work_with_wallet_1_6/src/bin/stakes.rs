@@ -0,0 +1,65 @@
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::native_token::lamports_to_sol;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use work_with_wallet_1_6::get_pubkey;
+use work_with_wallet_1_6::stake::{ActivationContext, get_stake_accounts};
+
+/// Lists every stake account whose withdraw authority is the given address,
+/// or our wallet, with its activation state.
+pub fn main() {
+    let rpc_url = String::from("https://api.devnet.solana.com");
+    let connection = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+
+    let withdrawer = std::env::args().nth(1).unwrap_or_else(get_pubkey);
+    let withdrawer = Pubkey::from_str(&withdrawer).expect("Invalid public key");
+
+    let accounts = match get_stake_accounts(&connection, &withdrawer) {
+        Ok(accounts) => accounts,
+        Err(err) => {
+            eprintln!("Failed to get stake accounts: {}", err);
+            return;
+        }
+    };
+    let context = match ActivationContext::fetch(&connection) {
+        Ok(context) => context,
+        Err(err) => {
+            eprintln!("Failed to get stake history: {}", err);
+            return;
+        }
+    };
+
+    println!(
+        "Stake accounts of {} in epoch {}:",
+        withdrawer,
+        context.epoch()
+    );
+    let (mut total, mut total_active) = (0, 0);
+    for account in &accounts {
+        let activation = context.activation(&account.state);
+        let voter = account
+            .state
+            .delegation()
+            .map(|delegation| delegation.voter_pubkey.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{} {:>14} SOL  {:<13} active {} SOL, activating {} SOL, deactivating {} SOL, vote account {}",
+            account.address,
+            lamports_to_sol(account.lamports),
+            activation.state,
+            lamports_to_sol(activation.effective),
+            lamports_to_sol(activation.activating),
+            lamports_to_sol(activation.deactivating),
+            voter
+        );
+        total += account.lamports;
+        total_active += activation.effective;
+    }
+    println!(
+        "{} accounts, {} SOL in total, {} SOL active",
+        accounts.len(),
+        lamports_to_sol(total),
+        lamports_to_sol(total_active)
+    );
+}
@@ -0,0 +1,236 @@
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::{clock::Epoch, feature, pubkey, pubkey::Pubkey, sysvar};
+use solana_stake_interface::program as stake_program;
+use solana_stake_interface::stake_history::StakeHistory;
+use solana_stake_interface::state::StakeStateV2;
+use std::error::Error;
+
+/// Offset of the withdraw authority in a stake account: the state tag, the
+/// rent exempt reserve and the stake authority come first.
+const WITHDRAWER_OFFSET: usize = 4 + 8 + 32;
+
+/// Feature that lowered the warmup and cooldown rate from 25% to 9%, the
+/// activation math depends on the epoch it was enabled in.
+const REDUCE_STAKE_WARMUP_COOLDOWN: Pubkey =
+    pubkey!("GwtDQBghCTBgmX2cpEGNPxTEBUTQRaDMGTr5qychdGMj");
+
+/// A stake account with its decoded state.
+pub struct StakeAccount {
+    pub address: Pubkey,
+    pub lamports: u64,
+    pub state: StakeStateV2,
+}
+
+/// Fetches and decodes a single stake account.
+pub fn get_stake_account(
+    rpc_client: &RpcClient,
+    address: &Pubkey,
+) -> Result<StakeAccount, Box<dyn Error>> {
+    let account = rpc_client.get_account(address)?;
+    if account.owner != stake_program::id() {
+        return Err(format!("{} is not a stake account", address).into());
+    }
+    Ok(StakeAccount {
+        address: *address,
+        lamports: account.lamports,
+        state: bincode::deserialize(&account.data)?,
+    })
+}
+
+/// Fetches every stake account whose withdraw authority is `withdrawer`.
+pub fn get_stake_accounts(
+    rpc_client: &RpcClient,
+    withdrawer: &Pubkey,
+) -> Result<Vec<StakeAccount>, Box<dyn Error>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            WITHDRAWER_OFFSET,
+            withdrawer.as_ref(),
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    rpc_client
+        .get_program_accounts_with_config(&stake_program::id(), config)?
+        .into_iter()
+        .map(|(address, account)| {
+            Ok(StakeAccount {
+                address,
+                lamports: account.lamports,
+                state: bincode::deserialize(&account.data)?,
+            })
+        })
+        .collect()
+}
+
+/// Activation of a stake account in the current epoch.
+pub struct Activation {
+    pub state: &'static str,
+    pub effective: u64,
+    pub activating: u64,
+    pub deactivating: u64,
+}
+
+/// Everything needed to work out how far stake has warmed up or cooled down,
+/// the RPC method that used to do this was removed.
+pub struct ActivationContext {
+    epoch: Epoch,
+    history: StakeHistory,
+    new_rate_activation_epoch: Option<Epoch>,
+}
+
+impl ActivationContext {
+    pub fn fetch(rpc_client: &RpcClient) -> Result<Self, Box<dyn Error>> {
+        let epoch = rpc_client.get_epoch_info()?.epoch;
+        let history =
+            bincode::deserialize(&rpc_client.get_account_data(&sysvar::stake_history::id())?)?;
+
+        let feature_account = rpc_client
+            .get_account_with_commitment(&REDUCE_STAKE_WARMUP_COOLDOWN, rpc_client.commitment())?
+            .value;
+        let new_rate_activation_epoch = match feature_account
+            .as_ref()
+            .and_then(feature::from_account)
+            .and_then(|feature| feature.activated_at)
+        {
+            Some(slot) => Some(rpc_client.get_epoch_schedule()?.get_epoch(slot)),
+            None => None,
+        };
+
+        Ok(ActivationContext {
+            epoch,
+            history,
+            new_rate_activation_epoch,
+        })
+    }
+
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    pub fn activation(&self, state: &StakeStateV2) -> Activation {
+        let Some(delegation) = state.delegation() else {
+            let state = match state {
+                StakeStateV2::Initialized(_) => "initialized",
+                _ => "uninitialized",
+            };
+            return Activation {
+                state,
+                effective: 0,
+                activating: 0,
+                deactivating: 0,
+            };
+        };
+
+        let status = delegation.stake_activating_and_deactivating(
+            self.epoch,
+            &self.history,
+            self.new_rate_activation_epoch,
+        );
+        let state = if status.deactivating > 0 {
+            "deactivating"
+        } else if status.activating > 0 {
+            "activating"
+        } else if status.effective > 0 {
+            "active"
+        } else {
+            "inactive"
+        };
+        Activation {
+            state,
+            effective: status.effective,
+            activating: status.activating,
+            deactivating: status.deactivating,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_stake_interface::stake_flags::StakeFlags;
+    use solana_stake_interface::stake_history::StakeHistoryEntry;
+    use solana_stake_interface::state::{Delegation, Meta, Stake};
+
+    const STAKE: u64 = 1_000_000_000;
+
+    /// A cluster so large that any of our stake warms up or cools down within
+    /// one epoch.
+    fn context(epoch: Epoch) -> ActivationContext {
+        let mut history = StakeHistory::default();
+        for epoch in 0..epoch {
+            history.add(
+                epoch,
+                StakeHistoryEntry {
+                    effective: 1_000 * STAKE,
+                    activating: STAKE,
+                    deactivating: STAKE,
+                },
+            );
+        }
+        ActivationContext {
+            epoch,
+            history,
+            new_rate_activation_epoch: None,
+        }
+    }
+
+    fn delegated(activation_epoch: Epoch, deactivation_epoch: Epoch) -> StakeStateV2 {
+        let mut delegation = Delegation::new(&Pubkey::new_unique(), STAKE, activation_epoch);
+        delegation.deactivation_epoch = deactivation_epoch;
+        StakeStateV2::Stake(
+            Meta::default(),
+            Stake {
+                delegation,
+                credits_observed: 0,
+            },
+            StakeFlags::empty(),
+        )
+    }
+
+    #[test]
+    fn initialized() {
+        let activation = context(10).activation(&StakeStateV2::Initialized(Meta::default()));
+        assert_eq!(activation.state, "initialized");
+        assert_eq!(activation.effective, 0);
+    }
+
+    #[test]
+    fn activating() {
+        let activation = context(10).activation(&delegated(10, u64::MAX));
+        assert_eq!(activation.state, "activating");
+        assert_eq!(activation.effective, 0);
+        assert_eq!(activation.activating, STAKE);
+    }
+
+    #[test]
+    fn active() {
+        let activation = context(10).activation(&delegated(5, u64::MAX));
+        assert_eq!(activation.state, "active");
+        assert_eq!(activation.effective, STAKE);
+        assert_eq!(activation.activating, 0);
+    }
+
+    #[test]
+    fn deactivating() {
+        let activation = context(10).activation(&delegated(5, 10));
+        assert_eq!(activation.state, "deactivating");
+        assert_eq!(activation.effective, STAKE);
+        assert_eq!(activation.deactivating, STAKE);
+    }
+
+    #[test]
+    fn inactive() {
+        let activation = context(12).activation(&delegated(5, 10));
+        assert_eq!(activation.state, "inactive");
+        assert_eq!(activation.effective, 0);
+        assert_eq!(activation.deactivating, 0);
+    }
+}
@@ -0,0 +1,210 @@
+use scripts_2_1_2_6::cli::{Args, fail, parse_pubkey};
+use scripts_2_1_2_6::load_keypair;
+use scripts_2_1_2_6::send::{SendError, TransactionSender};
+use scripts_2_1_2_6::token::{
+    MintInfo, TokenAccountInfo, format_amount, get_mint, get_token_account,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction, program_option::COption, pubkey::Pubkey, signature::Signer,
+};
+use spl_token_2022::instruction::{AuthorityType, freeze_account, set_authority, thaw_account};
+
+const USAGE: &str = "Usage: authority <command>
+
+Commands:
+  show <mint>
+  set-mint <mint> <new_authority|none> [--yes]
+  set-freeze <mint> <new_authority|none> [--yes]
+  freeze <token_account>
+  thaw <token_account>
+  set-close <token_account> <new_authority|none>
+
+Passing `none` revokes the authority for good, which needs --yes for mints.";
+
+fn main() {
+    let args = Args::from_env();
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+
+    match args.required(0, USAGE) {
+        "show" => show(&rpc_client, &args),
+        "set-mint" => set_mint_authority(&rpc_client, &args, AuthorityType::MintTokens),
+        "set-freeze" => set_mint_authority(&rpc_client, &args, AuthorityType::FreezeAccount),
+        "freeze" => freeze_or_thaw(&rpc_client, &args, true),
+        "thaw" => freeze_or_thaw(&rpc_client, &args, false),
+        "set-close" => set_close_authority(&rpc_client, &args),
+        _ => fail(USAGE),
+    }
+}
+
+fn show(rpc_client: &RpcClient, args: &Args) {
+    let mint = parse_pubkey(args.required(1, USAGE), "mint");
+    let info = fetch_mint(rpc_client, &mint);
+
+    println!("Mint: {}", mint);
+    println!("Token program: {}", info.token_program);
+    println!("Decimals: {}", info.mint.decimals);
+    println!(
+        "Supply: {}",
+        format_amount(info.mint.supply, info.mint.decimals)
+    );
+    println!("Mint authority: {}", describe(info.mint.mint_authority));
+    println!("Freeze authority: {}", describe(info.mint.freeze_authority));
+}
+
+fn set_mint_authority(rpc_client: &RpcClient, args: &Args, authority_type: AuthorityType) {
+    let mint = parse_pubkey(args.required(1, USAGE), "mint");
+    let new_authority = parse_new_authority(args.required(2, USAGE));
+    let authority = load_keypair();
+
+    let info = fetch_mint(rpc_client, &mint);
+    let (name, current) = match authority_type {
+        AuthorityType::MintTokens => ("Mint authority", info.mint.mint_authority),
+        _ => ("Freeze authority", info.mint.freeze_authority),
+    };
+    check_authority(name, current, &authority.pubkey());
+
+    if new_authority.is_none() && !args.flag("yes") {
+        fail(&format!(
+            "Revoking the {} of {} cannot be undone, pass --yes to confirm",
+            name.to_lowercase(),
+            mint
+        ));
+    }
+
+    let instruction = set_authority(
+        &info.token_program,
+        &mint,
+        new_authority.as_ref(),
+        authority_type,
+        &authority.pubkey(),
+        &[],
+    )
+    .unwrap();
+
+    let success = match new_authority {
+        Some(new_authority) => format!("{} set to {}", name, new_authority),
+        None => format!("{} revoked", name),
+    };
+    send(rpc_client, args, instruction, &authority, &success);
+}
+
+fn freeze_or_thaw(rpc_client: &RpcClient, args: &Args, freeze: bool) {
+    let address = parse_pubkey(args.required(1, USAGE), "token account");
+    let authority = load_keypair();
+
+    let token_account = fetch_token_account(rpc_client, &address);
+    let mint = token_account.account.mint;
+    let info = fetch_mint(rpc_client, &mint);
+    check_authority(
+        "Freeze authority",
+        info.mint.freeze_authority,
+        &authority.pubkey(),
+    );
+
+    let build = if freeze { freeze_account } else { thaw_account };
+    let instruction = build(
+        &token_account.token_program,
+        &address,
+        &mint,
+        &authority.pubkey(),
+        &[],
+    )
+    .unwrap();
+
+    let success = if freeze {
+        format!("Token account {} frozen", address)
+    } else {
+        format!("Token account {} thawed", address)
+    };
+    send(rpc_client, args, instruction, &authority, &success);
+}
+
+fn set_close_authority(rpc_client: &RpcClient, args: &Args) {
+    let address = parse_pubkey(args.required(1, USAGE), "token account");
+    let new_authority = parse_new_authority(args.required(2, USAGE));
+    let owner = load_keypair();
+
+    let token_account = fetch_token_account(rpc_client, &address);
+    // Without a close authority the owner may close the account, so the owner
+    // is the one who can hand that right over.
+    let current = match token_account.account.close_authority {
+        COption::Some(close_authority) => close_authority,
+        COption::None => token_account.account.owner,
+    };
+    check_authority("Close authority", COption::Some(current), &owner.pubkey());
+
+    let instruction = set_authority(
+        &token_account.token_program,
+        &address,
+        new_authority.as_ref(),
+        AuthorityType::CloseAccount,
+        &owner.pubkey(),
+        &[],
+    )
+    .unwrap();
+
+    let success = match new_authority {
+        Some(new_authority) => format!("Close authority set to {}", new_authority),
+        None => "Close authority removed, the owner can close the account".to_string(),
+    };
+    send(rpc_client, args, instruction, &owner, &success);
+}
+
+/// `none` revokes an authority, anything else must be an address.
+fn parse_new_authority(value: &str) -> Option<Pubkey> {
+    match value {
+        "none" => None,
+        _ => Some(parse_pubkey(value, "new authority")),
+    }
+}
+
+fn describe(authority: COption<Pubkey>) -> String {
+    match authority {
+        COption::Some(authority) => authority.to_string(),
+        COption::None => "none".to_string(),
+    }
+}
+
+fn check_authority(name: &str, current: COption<Pubkey>, signer: &Pubkey) {
+    match current {
+        COption::Some(current) if current == *signer => {}
+        COption::Some(current) => fail(&format!(
+            "{} is {}, not our wallet {}",
+            name, current, signer
+        )),
+        COption::None => fail(&format!("{} has been revoked", name)),
+    }
+}
+
+fn fetch_mint(rpc_client: &RpcClient, mint: &Pubkey) -> MintInfo {
+    get_mint(rpc_client, mint)
+        .unwrap_or_else(|err| fail(&format!("Failed to read mint {}: {}", mint, err)))
+}
+
+fn fetch_token_account(rpc_client: &RpcClient, address: &Pubkey) -> TokenAccountInfo {
+    get_token_account(rpc_client, address).unwrap_or_else(|err| {
+        fail(&format!(
+            "Failed to read token account {}: {}",
+            address, err
+        ))
+    })
+}
+
+fn send(
+    rpc_client: &RpcClient,
+    args: &Args,
+    instruction: Instruction,
+    authority: &impl Signer,
+    success: &str,
+) {
+    let sender = TransactionSender::new(rpc_client, args.flag("dry-run"));
+    match sender.send(&[instruction], &authority.pubkey(), &[authority]) {
+        Ok(signature) => {
+            println!("✅ {}!", success);
+            println!("Transaction Signature: {}", signature);
+        }
+        Err(SendError::DryRun) => {}
+        Err(err) => eprintln!("❌ Failed: {}", err),
+    }
+}
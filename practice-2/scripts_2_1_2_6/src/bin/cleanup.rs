@@ -0,0 +1,230 @@
+use scripts_2_1_2_6::cli::{Args, fail};
+use scripts_2_1_2_6::load_keypair;
+use scripts_2_1_2_6::send::{SendError, TransactionSender};
+use scripts_2_1_2_6::token::{
+    TokenAccountInfo, format_amount, get_mints, get_token_accounts_by_owner, parse_ui_amount,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction, native_token::lamports_to_sol, pubkey::Pubkey, signature::Signer,
+};
+use spl_token_2022::instruction::{burn_checked, close_account};
+use spl_token_2022::state::{AccountState, Mint};
+use std::collections::HashMap;
+
+const USAGE: &str = "Usage: cleanup [--dust=<amount>] [--dry-run]
+
+Closes empty token accounts of our wallet and returns their rent.  With
+--dust, balances up to <amount> whole tokens are burned first, never those of
+NFTs.  With --dry-run the transactions are only simulated.";
+
+// Each account needs up to two instructions, this keeps transactions small.
+const ACCOUNTS_PER_TRANSACTION: usize = 6;
+
+/// `--dust` in minor units at its own precision, `0.01` is 1 at 2 decimals.
+struct DustThreshold {
+    amount: u64,
+    decimals: u8,
+}
+
+impl DustThreshold {
+    fn parse(dust: &str) -> Option<Self> {
+        // `1.50` is as precise as `1.5`.
+        let dust = match dust.split_once('.') {
+            Some(_) => dust.trim_end_matches('0'),
+            None => dust,
+        };
+        let decimals = dust
+            .split_once('.')
+            .map_or(0, |(_, fraction)| fraction.len());
+        let decimals = u8::try_from(decimals).ok()?;
+        Some(DustThreshold {
+            amount: parse_ui_amount(dust, decimals)?,
+            decimals,
+        })
+    }
+
+    /// The threshold in minor units of a mint with `decimals`, `None` when the
+    /// mint can't represent it.
+    fn for_mint(&self, decimals: u8) -> Option<u64> {
+        let scale = 10_u64.checked_pow(decimals.checked_sub(self.decimals)? as u32)?;
+        self.amount.checked_mul(scale)
+    }
+}
+
+enum Action {
+    Close,
+    BurnAndClose(u64),
+    Skip(String),
+    Keep,
+}
+
+fn main() {
+    let args = Args::from_env();
+    if args.positional(0).is_some() {
+        fail(USAGE);
+    }
+    let dust = args.option("dust").map(|dust| {
+        DustThreshold::parse(dust)
+            .unwrap_or_else(|| fail(&format!("Invalid dust amount: {}", dust)))
+    });
+
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+    let owner = load_keypair();
+
+    let accounts = get_token_accounts_by_owner(&rpc_client, &owner.pubkey())
+        .unwrap_or_else(|err| fail(&format!("Failed to list token accounts: {}", err)));
+
+    let mut mint_addresses: Vec<Pubkey> = accounts.iter().map(|info| info.account.mint).collect();
+    mint_addresses.sort();
+    mint_addresses.dedup();
+    let mints: HashMap<Pubkey, Mint> = get_mints(&rpc_client, &mint_addresses)
+        .unwrap_or_else(|err| fail(&format!("Failed to read mints: {}", err)))
+        .into_iter()
+        .map(|mint| (mint.address, mint.mint))
+        .collect();
+
+    println!(
+        "Found {} token accounts for {}",
+        accounts.len(),
+        owner.pubkey()
+    );
+
+    let mut to_clean = Vec::new();
+    for info in &accounts {
+        let Some(mint) = mints.get(&info.account.mint) else {
+            println!("⏭️  {} skipped: mint not found", info.address);
+            continue;
+        };
+        let mint_decimals = mint.decimals;
+        // `Err` says why a balance is kept although `--dust` is set.
+        let dust_amount = match &dust {
+            None => Ok(None),
+            Some(_) if mint.decimals == 0 || mint.supply == 1 => {
+                Err("never burned, the mint looks like an NFT")
+            }
+            Some(dust) => dust
+                .for_mint(mint.decimals)
+                .map(Some)
+                .ok_or("dust amount can't be represented with the decimals of the mint"),
+        };
+        let balance = format_amount(info.account.amount, mint_decimals);
+
+        match classify(info, &owner.pubkey(), dust_amount.unwrap_or(None)) {
+            Action::Close => {
+                println!("🧹 {} is empty, mint {}", info.address, info.account.mint);
+                to_clean.push((info, Action::Close, mint_decimals));
+            }
+            Action::BurnAndClose(amount) => {
+                println!(
+                    "🔥 {} holds dust {}, mint {}",
+                    info.address, balance, info.account.mint
+                );
+                to_clean.push((info, Action::BurnAndClose(amount), mint_decimals));
+            }
+            Action::Skip(reason) => println!("⏭️  {} skipped: {}", info.address, reason),
+            Action::Keep => {
+                if let Err(reason) = dust_amount {
+                    println!("⏭️  {} skipped: {}", info.address, reason);
+                }
+            }
+        }
+    }
+
+    let reclaimable: u64 = to_clean.iter().map(|(info, _, _)| info.lamports).sum();
+    println!(
+        "{} accounts can be closed, reclaiming {} lamports ({} SOL)",
+        to_clean.len(),
+        reclaimable,
+        lamports_to_sol(reclaimable)
+    );
+
+    if to_clean.is_empty() {
+        return;
+    }
+
+    let sender = TransactionSender::new(&rpc_client, args.flag("dry-run"));
+    let mut reclaimed = 0;
+    for batch in to_clean.chunks(ACCOUNTS_PER_TRANSACTION) {
+        let instructions: Vec<Instruction> = batch
+            .iter()
+            .flat_map(|(info, action, decimals)| {
+                cleanup_instructions(info, action, *decimals, &owner.pubkey())
+            })
+            .collect();
+
+        match sender.send(&instructions, &owner.pubkey(), &[&owner]) {
+            Ok(signature) => {
+                reclaimed += batch.iter().map(|(info, _, _)| info.lamports).sum::<u64>();
+                println!("✅ Closed {} accounts: {}", batch.len(), signature);
+            }
+            Err(SendError::DryRun) => {}
+            Err(err) => eprintln!("❌ Failed to close {} accounts: {}", batch.len(), err),
+        }
+    }
+    if sender.is_dry_run() {
+        return;
+    }
+
+    println!(
+        "Reclaimed {} lamports ({} SOL)",
+        reclaimed,
+        lamports_to_sol(reclaimed)
+    );
+}
+
+/// Decides what to do with one of our token accounts.
+///
+/// Frozen accounts cannot be closed.  Accounts with a delegate or with a close
+/// authority other than us are treated as vault-like, someone else relies on
+/// them.  Wrapped SOL accounts are left alone, they are not worth burning.
+fn classify(info: &TokenAccountInfo, owner: &Pubkey, dust: Option<u64>) -> Action {
+    let account = &info.account;
+
+    if account.state == AccountState::Frozen {
+        return Action::Skip("frozen".to_string());
+    }
+    if account.is_native() {
+        return Action::Skip("wrapped SOL".to_string());
+    }
+    if let Some(close_authority) = Option::<Pubkey>::from(account.close_authority)
+        .filter(|close_authority| close_authority != owner)
+    {
+        return Action::Skip(format!("close authority is {}", close_authority));
+    }
+    if let Some(delegate) = Option::<Pubkey>::from(account.delegate) {
+        return Action::Skip(format!("delegated to {}", delegate));
+    }
+
+    match dust {
+        _ if account.amount == 0 => Action::Close,
+        Some(dust) if account.amount <= dust => Action::BurnAndClose(account.amount),
+        _ => Action::Keep,
+    }
+}
+
+fn cleanup_instructions(
+    info: &TokenAccountInfo,
+    action: &Action,
+    decimals: u8,
+    owner: &Pubkey,
+) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    if let Action::BurnAndClose(amount) = action {
+        instructions.push(
+            burn_checked(
+                &info.token_program,
+                &info.address,
+                &info.account.mint,
+                owner,
+                &[],
+                *amount,
+                decimals,
+            )
+            .unwrap(),
+        );
+    }
+    instructions
+        .push(close_account(&info.token_program, &info.address, owner, owner, &[]).unwrap());
+    instructions
+}
@@ -1,14 +1,16 @@
+use scripts_2_1_2_6::cli::Args;
 use scripts_2_1_2_6::load_keypair;
+use scripts_2_1_2_6::send::{SendError, TransactionSender};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signer;
-use solana_sdk::transaction::Transaction;
 use spl_associated_token_account::{
     get_associated_token_address, instruction::create_associated_token_account,
 };
 use std::str::FromStr;
 
 fn main() {
+    let args = Args::from_env();
     let rpc_url = "https://api.devnet.solana.com";
     let rpc_client = RpcClient::new(rpc_url);
     let recipient = Pubkey::from_str("3iUzRvC7CoTroUFZ6Ncs4pxPpEg7JzxeWMWQxCM6XiRp").unwrap();
@@ -30,26 +32,20 @@ fn main() {
                 &mint_pubkey,
                 &associated_token_address,
             );
-            // Fetch the latest blockhash
-            let blockhash = rpc_client.get_latest_blockhash().unwrap();
-
-            // Build and sign the transaction
-            let transaction = Transaction::new_signed_with_payer(
+            // Simulate, sign, send and confirm the transaction
+            match TransactionSender::new(&rpc_client, args.flag("dry-run")).send(
                 &[instruction],
-                Some(&owner.pubkey()),
-                &[owner],
-                blockhash,
-            );
-
-            // Send and confirm the transaction
-            match rpc_client.send_and_confirm_transaction(&transaction) {
+                &owner.pubkey(),
+                &[&owner],
+            ) {
                 Ok(signature) => {
                     println!("✅ Associated Token Account Created Successfully!");
                     println!("Address: {}", associated_token_address);
                     println!("Transaction Signature: {}", signature);
                 }
+                Err(SendError::DryRun) => {}
                 Err(err) => {
-                    eprintln!("❌ Failed to Create Associated Token Account: {:?}", err);
+                    eprintln!("❌ Failed to Create Associated Token Account: {}", err);
                 }
             };
         }
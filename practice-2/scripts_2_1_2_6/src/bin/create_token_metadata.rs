@@ -1,13 +1,16 @@
 use mpl_token_metadata::ID as metadata_program_id;
 use mpl_token_metadata::instructions::CreateMetadataAccountV3Builder;
 use mpl_token_metadata::types::DataV2;
+use scripts_2_1_2_6::cli::Args;
 use scripts_2_1_2_6::load_keypair;
+use scripts_2_1_2_6::send::{SendError, TransactionSender};
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::{pubkey::Pubkey, signature::Signer, transaction::Transaction};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
 use std::process::exit;
 use std::str::FromStr;
 
 fn main() {
+    let args = Args::from_env();
     let rpc_client = RpcClient::new("https://api.devnet.solana.com");
     let mint = Pubkey::from_str("EbqXNvEBmVox6e85Z6UBCFeCZDpYXHgkBEF9LK139kzu").unwrap();
 
@@ -23,15 +26,12 @@ fn main() {
     .0;
 
     // Check if the Metadata PDA already exists
-    match rpc_client.get_account(&metadata_address) {
-        Ok(_) => {
-            println!(
-                "✅ Token metadata already exitsts for this mint: https://explorer.solana.com/address/{}?cluster=devnet",
-                mint
-            );
-            exit(0);
-        }
-        Err(_) => {}
+    if rpc_client.get_account(&metadata_address).is_ok() {
+        println!(
+            "✅ Token metadata already exitsts for this mint: https://explorer.solana.com/address/{}?cluster=devnet",
+            mint
+        );
+        exit(0);
     }
 
     let mint_authority = load_keypair();
@@ -56,25 +56,19 @@ fn main() {
         .is_mutable(true)
         .instruction();
 
-    // Fetch the latest blockhash
-    let blockhash = rpc_client.get_latest_blockhash().unwrap();
-
-    // Create a transaction with instructions
-    let transaction = Transaction::new_signed_with_payer(
+    // Simulate, sign, send and confirm the transaction
+    match TransactionSender::new(&rpc_client, args.flag("dry-run")).send(
         &[create_metadata_account_instruction], // Instructions (in this case, metadata creation)
-        Some(&mint_authority.pubkey()),         // Fee payer
+        &mint_authority.pubkey(),               // Fee payer
         &[&mint_authority],                     // Signers (user in this case)
-        blockhash,                              // Recent blockhash
-    );
-
-    // Send and confirm the transaction
-    match rpc_client.send_and_confirm_transaction(&transaction) {
+    ) {
         Ok(signature) => {
             println!("✅ Token metadata created successfully!");
             println!("Transaction Signature: {}", signature);
         }
+        Err(SendError::DryRun) => return,
         Err(err) => {
-            eprintln!("❌ Failed to create token metadata: {:?}", err);
+            eprintln!("❌ Failed to create token metadata: {}", err);
         }
     };
 
@@ -1,14 +1,17 @@
+use scripts_2_1_2_6::cli::Args;
 use scripts_2_1_2_6::load_keypair;
+use scripts_2_1_2_6::send::{SendError, TransactionSender};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     signature::{Keypair, Signer},
     system_instruction,
-    transaction::Transaction,
 };
 
 use spl_token::instruction::initialize_mint;
+use std::process::exit;
 
 fn main() {
+    let args = Args::from_env();
     let sender = load_keypair();
 
     println!("🔑 Our public key is: {}", sender.pubkey());
@@ -17,8 +20,6 @@ fn main() {
     let rpc_url = "https://api.devnet.solana.com";
     let rpc_client = RpcClient::new(rpc_url);
 
-    let blockhash = rpc_client.get_latest_blockhash().unwrap();
-
     // Create a new token mint account
     let mint_keypair = Keypair::new();
     const MINT_ACCOUNT_SIZE: usize = 82;
@@ -47,16 +48,19 @@ fn main() {
     .unwrap();
 
     // Build and send the transaction
-    let transaction = Transaction::new_signed_with_payer(
+    let result = TransactionSender::new(&rpc_client, args.flag("dry-run")).send(
         &[create_account_instruction, initialize_mint_instruction],
-        Some(&sender.pubkey()),
+        &sender.pubkey(),
         &[&sender, &mint_keypair as &dyn Signer],
-        blockhash,
     );
-
-    rpc_client
-        .send_and_confirm_transaction(&transaction)
-        .unwrap();
+    match result {
+        Ok(_) => {}
+        Err(SendError::DryRun) => return,
+        Err(err) => {
+            eprintln!("❌ Failed to create token mint: {}", err);
+            exit(1);
+        }
+    }
 
     println!("✅ Token Mint Created: {}", mint_keypair.pubkey());
 
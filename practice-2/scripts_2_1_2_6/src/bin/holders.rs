@@ -0,0 +1,80 @@
+use scripts_2_1_2_6::cli::{Args, fail, parse, parse_pubkey};
+use scripts_2_1_2_6::snapshot::take_snapshot;
+use scripts_2_1_2_6::token::{format_amount, get_mint};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::fs;
+
+const USAGE: &str = "Usage: holders <mint> [--format=csv|json] [--output=<file>] [--slot=<slot>]
+                      [--top=<count>] [--exclude=<owner>,<owner>...]
+
+Writes a snapshot of all holders of <mint>.  With --slot the snapshot is taken
+once the RPC node has reached that slot, the slot actually used is recorded.
+Balances of --exclude owners do not count as circulating.";
+
+fn main() {
+    let args = Args::from_env();
+    let mint = parse_pubkey(args.required(0, USAGE), "mint");
+    let format = args.option("format").unwrap_or("csv");
+    if format != "csv" && format != "json" {
+        fail(USAGE);
+    }
+    let min_slot = args.option("slot").map(|slot| parse(slot, "slot"));
+    let top: usize = args
+        .option("top")
+        .map(|top| parse(top, "top"))
+        .unwrap_or(10);
+    let excluded: Vec<Pubkey> = args
+        .option("exclude")
+        .map(|owners| {
+            owners
+                .split(',')
+                .map(|owner| parse_pubkey(owner, "excluded owner"))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+
+    let mint_info = get_mint(&rpc_client, &mint)
+        .unwrap_or_else(|err| fail(&format!("Failed to read mint {}: {}", mint, err)));
+    let snapshot = take_snapshot(&rpc_client, &mint_info, min_slot, &excluded)
+        .unwrap_or_else(|err| fail(&format!("Failed to read token accounts: {}", err)));
+    let decimals = snapshot.decimals;
+
+    println!("Mint: {}", snapshot.mint);
+    println!("Slot: {}", snapshot.slot);
+    println!("Supply: {}", format_amount(snapshot.supply, decimals));
+    println!(
+        "Circulating: {}",
+        format_amount(snapshot.circulating, decimals)
+    );
+    println!(
+        "Holders: {} ({} token accounts)",
+        snapshot.holders.len(),
+        snapshot.token_accounts
+    );
+    println!("Top holders:");
+    for (rank, holder) in snapshot.holders.iter().take(top).enumerate() {
+        println!(
+            "  {:>3}. {} {} ({:.2}%)",
+            rank + 1,
+            holder.owner,
+            holder.ui_amount,
+            holder.share
+        );
+    }
+
+    let output = args
+        .option("output")
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("./snapshot_{}_{}.{}", mint, snapshot.slot, format));
+    let contents = match format {
+        "json" => serde_json::to_string_pretty(&snapshot).unwrap(),
+        _ => snapshot.to_csv(),
+    };
+    match fs::write(&output, contents) {
+        Ok(()) => println!("✅ Snapshot written to {}", output),
+        Err(err) => fail(&format!("Failed to write {}: {}", output, err)),
+    }
+}
@@ -0,0 +1,296 @@
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use scripts_2_1_2_6::cli::{Args, fail};
+use scripts_2_1_2_6::get_transaction;
+use scripts_2_1_2_6::inspect::decode_instruction;
+use scripts_2_1_2_6::lookup_table::fetch_lookup_table;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::{
+    bs58, instruction::CompiledInstruction, message::VersionedMessage,
+    native_token::lamports_to_sol, pubkey::Pubkey, signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status_client_types::{
+    UiInnerInstructions, UiInstruction, UiLoadedAddresses, UiTransactionStatusMeta,
+    UiTransactionTokenBalance,
+};
+use std::str::FromStr;
+
+const USAGE: &str = "Usage: inspect <signature|base64 transaction>
+
+A signature is looked up on chain.  A base64 transaction, for example one
+written by `nonce_account build-transfer`, is simulated instead of sent.";
+
+/// What we know about a transaction besides its instructions.
+struct Outcome {
+    error: Option<String>,
+    fee: Option<u64>,
+    compute_units: Option<u64>,
+    inner_instructions: Vec<UiInnerInstructions>,
+    logs: Vec<String>,
+    meta: Option<UiTransactionStatusMeta>,
+}
+
+fn main() {
+    let args = Args::from_env();
+    let input = args.required(0, USAGE);
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+
+    let (transaction, account_keys, outcome) = match Signature::from_str(input) {
+        Ok(signature) => fetch(&rpc_client, &signature),
+        Err(_) => simulate(&rpc_client, input),
+    };
+
+    match &outcome.error {
+        None => println!("Status: ✅ success"),
+        Some(error) => println!("Status: ❌ failed: {}", error),
+    }
+    if let Some(fee) = outcome.fee {
+        println!("Fee: {} lamports", fee);
+    }
+    if let Some(units) = outcome.compute_units {
+        println!("Compute units: {}", units);
+    }
+
+    println!("Instructions:");
+    for (index, instruction) in transaction.message.instructions().iter().enumerate() {
+        print_instruction(&format!("#{}", index + 1), instruction, &account_keys);
+        for inner in outcome
+            .inner_instructions
+            .iter()
+            .filter(|inner| inner.index as usize == index)
+        {
+            for (inner_index, instruction) in inner.instructions.iter().enumerate() {
+                let UiInstruction::Compiled(instruction) = instruction else {
+                    continue;
+                };
+                let compiled = CompiledInstruction {
+                    program_id_index: instruction.program_id_index,
+                    accounts: instruction.accounts.clone(),
+                    data: bs58::decode(&instruction.data)
+                        .into_vec()
+                        .unwrap_or_default(),
+                };
+                let label = format!("  ↳ #{}.{}", index + 1, inner_index + 1);
+                print_instruction(&label, &compiled, &account_keys);
+            }
+        }
+    }
+
+    match &outcome.meta {
+        Some(meta) => print_balance_changes(meta, &account_keys),
+        None => println!("Balance changes: only shown for landed transactions"),
+    }
+
+    println!("Logs:");
+    for log in &outcome.logs {
+        println!("  {}", log);
+    }
+}
+
+/// Looks up a confirmed transaction.
+fn fetch(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+) -> (VersionedTransaction, Vec<Pubkey>, Outcome) {
+    let confirmed = get_transaction(rpc_client, signature)
+        .unwrap_or_else(|err| fail(&format!("Failed to fetch {}: {}", signature, err)));
+    let transaction = confirmed
+        .transaction
+        .transaction
+        .decode()
+        .unwrap_or_else(|| fail("Failed to decode the transaction"));
+    let meta = confirmed
+        .transaction
+        .meta
+        .unwrap_or_else(|| fail("The RPC node returned no status for this transaction"));
+
+    println!("Signature: {}", signature);
+    println!("Slot: {}", confirmed.slot);
+    if let Some(block_time) = confirmed.block_time {
+        println!("Block time: {}", block_time);
+    }
+
+    // Accounts loaded from lookup tables follow the static ones, writable
+    // first.
+    let mut account_keys = transaction.message.static_account_keys().to_vec();
+    if let Some(loaded) = Option::<UiLoadedAddresses>::from(meta.loaded_addresses.clone()) {
+        account_keys.extend(
+            loaded
+                .writable
+                .iter()
+                .chain(&loaded.readonly)
+                .filter_map(|address| Pubkey::from_str(address).ok()),
+        );
+    }
+
+    let outcome = Outcome {
+        error: meta.err.as_ref().map(ToString::to_string),
+        fee: Some(meta.fee),
+        compute_units: Option::from(meta.compute_units_consumed.clone()),
+        inner_instructions: Option::from(meta.inner_instructions.clone()).unwrap_or_default(),
+        logs: Option::from(meta.log_messages.clone()).unwrap_or_default(),
+        meta: Some(meta),
+    };
+    (transaction, account_keys, outcome)
+}
+
+/// Simulates a serialized transaction without sending it.
+fn simulate(rpc_client: &RpcClient, encoded: &str) -> (VersionedTransaction, Vec<Pubkey>, Outcome) {
+    let transaction: VersionedTransaction = BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| bincode::deserialize(&bytes).ok())
+        .unwrap_or_else(|| fail(USAGE));
+
+    println!("Transaction (not sent)");
+
+    let mut account_keys = transaction.message.static_account_keys().to_vec();
+    let lookups = transaction
+        .message
+        .address_table_lookups()
+        .unwrap_or_default();
+    let mut readonly = Vec::new();
+    for lookup in lookups {
+        let (_, table) =
+            fetch_lookup_table(rpc_client, &lookup.account_key).unwrap_or_else(|err| {
+                fail(&format!(
+                    "Failed to read lookup table {}: {}",
+                    lookup.account_key, err
+                ))
+            });
+        let resolve = |index: &u8| table.addresses.get(*index as usize).copied();
+        account_keys.extend(lookup.writable_indexes.iter().filter_map(resolve));
+        readonly.extend(lookup.readonly_indexes.iter().filter_map(resolve));
+    }
+    account_keys.extend(readonly);
+
+    let result = rpc_client
+        .simulate_transaction_with_config(
+            &transaction,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                inner_instructions: true,
+                commitment: Some(rpc_client.commitment()),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .unwrap_or_else(|err| fail(&format!("Failed to simulate: {}", err)))
+        .value;
+
+    let fee = match &transaction.message {
+        VersionedMessage::Legacy(message) => rpc_client.get_fee_for_message(message),
+        VersionedMessage::V0(message) => rpc_client.get_fee_for_message(message),
+    };
+
+    let outcome = Outcome {
+        error: result.err.as_ref().map(ToString::to_string),
+        fee: fee.ok(),
+        compute_units: result.units_consumed,
+        inner_instructions: result.inner_instructions.unwrap_or_default(),
+        logs: result.logs.unwrap_or_default(),
+        meta: None,
+    };
+    (transaction, account_keys, outcome)
+}
+
+fn print_instruction(label: &str, instruction: &CompiledInstruction, account_keys: &[Pubkey]) {
+    let key = |index: u8| {
+        account_keys
+            .get(index as usize)
+            .copied()
+            .unwrap_or_default()
+    };
+    let program_id = key(instruction.program_id_index);
+    let decoded = decode_instruction(&program_id, &instruction.data);
+
+    println!("{} {}: {}", label, decoded.program, decoded.name);
+    let indent = " ".repeat(label.chars().count() + 1);
+    for (name, value) in &decoded.args {
+        println!("{}{}: {}", indent, name, value);
+    }
+    for (position, index) in instruction.accounts.iter().enumerate() {
+        match decoded.account_names.get(position) {
+            Some(name) => println!("{}{}: {}", indent, name, key(*index)),
+            None => println!("{}account {}: {}", indent, position, key(*index)),
+        }
+    }
+}
+
+/// The account at `index`, or the index itself if the keys we resolved don't
+/// reach that far.
+fn account_name(account_keys: &[Pubkey], index: usize) -> String {
+    account_keys
+        .get(index)
+        .map(ToString::to_string)
+        .unwrap_or_else(|| format!("account #{}", index))
+}
+
+fn print_balance_changes(meta: &UiTransactionStatusMeta, account_keys: &[Pubkey]) {
+    println!("SOL balance changes:");
+    for (index, (pre, post)) in meta
+        .pre_balances
+        .iter()
+        .zip(&meta.post_balances)
+        .enumerate()
+    {
+        if pre == post {
+            continue;
+        }
+        let change = *post as i128 - *pre as i128;
+        let sign = if change < 0 { "-" } else { "+" };
+        println!(
+            "  {}: {}{} SOL",
+            account_name(account_keys, index),
+            sign,
+            lamports_to_sol(change.unsigned_abs() as u64)
+        );
+    }
+
+    let pre_tokens: Vec<UiTransactionTokenBalance> =
+        Option::from(meta.pre_token_balances.clone()).unwrap_or_default();
+    let post_tokens: Vec<UiTransactionTokenBalance> =
+        Option::from(meta.post_token_balances.clone()).unwrap_or_default();
+
+    // A token account may only appear before (closed) or after (created).
+    let mut indexes: Vec<u8> = pre_tokens
+        .iter()
+        .chain(&post_tokens)
+        .map(|balance| balance.account_index)
+        .collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+
+    println!("Token balance changes:");
+    for index in indexes {
+        let find = |balances: &[UiTransactionTokenBalance]| {
+            balances
+                .iter()
+                .find(|balance| balance.account_index == index)
+                .cloned()
+        };
+        let (pre, post) = (find(&pre_tokens), find(&post_tokens));
+        let amount = |balance: &Option<UiTransactionTokenBalance>| {
+            balance
+                .as_ref()
+                .map(|balance| balance.ui_token_amount.ui_amount_string.clone())
+                .unwrap_or_else(|| "-".to_string())
+        };
+        if amount(&pre) == amount(&post) {
+            continue;
+        }
+        let Some(balance) = post.as_ref().or(pre.as_ref()) else {
+            continue;
+        };
+        let owner: Option<String> = Option::from(balance.owner.clone());
+        println!(
+            "  {} (mint {}, owner {}): {} → {}",
+            account_name(account_keys, index as usize),
+            balance.mint,
+            owner.unwrap_or_else(|| "unknown".to_string()),
+            amount(&pre),
+            amount(&post)
+        );
+    }
+}
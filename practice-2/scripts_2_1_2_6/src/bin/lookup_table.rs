@@ -0,0 +1,201 @@
+use scripts_2_1_2_6::cli::{Args, fail, parse_pubkey};
+use scripts_2_1_2_6::load_keypair;
+use scripts_2_1_2_6::lookup_table::{
+    fetch_lookup_table, forget_lookup_table, save_lookup_table, saved_lookup_tables,
+};
+use scripts_2_1_2_6::send::{SendError, TransactionSender};
+use solana_address_lookup_table_interface::instruction::{
+    close_lookup_table, create_lookup_table, deactivate_lookup_table, extend_lookup_table,
+};
+use solana_address_lookup_table_interface::state::estimate_last_valid_slot;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    clock::Slot, commitment_config::CommitmentConfig, instruction::Instruction, pubkey::Pubkey,
+    signature::Signer,
+};
+
+const USAGE: &str = "Usage: lookup_table <command>
+
+Commands:
+  create
+  extend <lookup_table> <address>...
+  deactivate <lookup_table>
+  close <lookup_table>
+  show <lookup_table>
+  list";
+
+// Keeps each `extend` transaction well below the transaction size limit.
+const ADDRESSES_PER_EXTEND: usize = 20;
+
+fn main() {
+    let args = Args::from_env();
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+
+    match args.required(0, USAGE) {
+        "create" => create(&rpc_client, &args),
+        "extend" => extend(&rpc_client, &args),
+        "deactivate" => deactivate(&rpc_client, &args),
+        "close" => close(&rpc_client, &args),
+        "show" => show(&rpc_client, &args),
+        "list" => list(&rpc_client),
+        _ => fail(USAGE),
+    }
+}
+
+fn create(rpc_client: &RpcClient, args: &Args) {
+    let authority = load_keypair();
+
+    // The table address is derived from a recent slot, it has to be in the
+    // `SlotHashes` sysvar, so we ask for a finalized one.
+    let recent_slot = rpc_client
+        .get_slot_with_commitment(CommitmentConfig::finalized())
+        .unwrap();
+    let (instruction, table) =
+        create_lookup_table(authority.pubkey(), authority.pubkey(), recent_slot);
+
+    if send(
+        rpc_client,
+        args,
+        instruction,
+        &authority,
+        "Lookup table created",
+    ) {
+        save_lookup_table(&table);
+        println!("Lookup table: {}", table);
+    }
+}
+
+fn extend(rpc_client: &RpcClient, args: &Args) {
+    let table = parse_pubkey(args.required(1, USAGE), "lookup table");
+    let authority = load_keypair();
+
+    let addresses: Vec<Pubkey> = (2..)
+        .map_while(|index| args.positional(index))
+        .map(|address| parse_pubkey(address, "address"))
+        .collect();
+    if addresses.is_empty() {
+        fail(USAGE);
+    }
+
+    for chunk in addresses.chunks(ADDRESSES_PER_EXTEND) {
+        let instruction = extend_lookup_table(
+            table,
+            authority.pubkey(),
+            Some(authority.pubkey()),
+            chunk.to_vec(),
+        );
+        let success = format!("Added {} addresses", chunk.len());
+        if !send(rpc_client, args, instruction, &authority, &success) {
+            return;
+        }
+    }
+}
+
+fn deactivate(rpc_client: &RpcClient, args: &Args) {
+    let table = parse_pubkey(args.required(1, USAGE), "lookup table");
+    let authority = load_keypair();
+
+    let instruction = deactivate_lookup_table(table, authority.pubkey());
+    if send(
+        rpc_client,
+        args,
+        instruction,
+        &authority,
+        "Lookup table deactivated",
+    ) {
+        forget_lookup_table(&table);
+        println!("It can be closed once the deactivation cooldown has passed.");
+    }
+}
+
+fn close(rpc_client: &RpcClient, args: &Args) {
+    let table = parse_pubkey(args.required(1, USAGE), "lookup table");
+    let authority = load_keypair();
+
+    let instruction = close_lookup_table(table, authority.pubkey(), authority.pubkey());
+    if send(
+        rpc_client,
+        args,
+        instruction,
+        &authority,
+        "Lookup table closed",
+    ) {
+        forget_lookup_table(&table);
+    }
+}
+
+fn show(rpc_client: &RpcClient, args: &Args) {
+    let table = parse_pubkey(args.required(1, USAGE), "lookup table");
+
+    let (meta, account) = fetch_lookup_table(rpc_client, &table)
+        .unwrap_or_else(|err| fail(&format!("Failed to read lookup table: {}", err)));
+
+    println!("Lookup table: {}", table);
+    match meta.authority {
+        Some(authority) => println!("Authority: {}", authority),
+        None => println!("Authority: none (frozen)"),
+    }
+    print_status(rpc_client, meta.deactivation_slot);
+    println!("Addresses ({}):", account.addresses.len());
+    for (index, address) in account.addresses.iter().enumerate() {
+        println!("  {:>3}: {}", index, address);
+    }
+}
+
+fn list(rpc_client: &RpcClient) {
+    let tables = saved_lookup_tables();
+    if tables.is_empty() {
+        println!("No saved lookup tables.");
+    }
+    for table in tables {
+        match fetch_lookup_table(rpc_client, &table) {
+            Ok((meta, account)) => {
+                println!("{}: {} addresses", table, account.addresses.len());
+                print_status(rpc_client, meta.deactivation_slot);
+            }
+            Err(err) => println!("{}: {}", table, err),
+        }
+    }
+}
+
+fn print_status(rpc_client: &RpcClient, deactivation_slot: Slot) {
+    if deactivation_slot == Slot::MAX {
+        println!("Status: active");
+        return;
+    }
+
+    let current_slot = rpc_client.get_slot().unwrap();
+    let closable_after = estimate_last_valid_slot(deactivation_slot);
+    if current_slot > closable_after {
+        println!("Status: deactivated, can be closed");
+    } else {
+        println!(
+            "Status: deactivating, can be closed in about {} slots",
+            closable_after - current_slot
+        );
+    }
+}
+
+/// Returns whether the transaction landed, a dry run did not.
+fn send(
+    rpc_client: &RpcClient,
+    args: &Args,
+    instruction: Instruction,
+    authority: &impl Signer,
+    success: &str,
+) -> bool {
+    // Lookup table management is always done with legacy transactions.
+    let sender = TransactionSender::new(rpc_client, args.flag("dry-run")).lookup_tables(Vec::new());
+    match sender.send(&[instruction], &authority.pubkey(), &[authority]) {
+        Ok(signature) => {
+            println!("✅ {}!", success);
+            println!("Transaction Signature: {}", signature);
+            true
+        }
+        Err(SendError::DryRun) => false,
+        Err(err) => {
+            eprintln!("❌ Failed: {}", err);
+            false
+        }
+    }
+}
@@ -0,0 +1,491 @@
+use scripts_2_1_2_6::cli::{Args, fail, parse_pubkey};
+use scripts_2_1_2_6::load_keypair;
+use scripts_2_1_2_6::nft::{NftMetadata, mint_nft_instructions, verify_collection_instruction};
+use scripts_2_1_2_6::send::{Attempt, SendError, TransactionSender, check_attempts};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+use spl_token::state::Mint;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const USAGE: &str =
+    "Usage: mint_nft <metadata.json> [--collection=<collection_mint>] [--as-collection]
+       mint_nft --batch=<folder> [--collection=<collection_mint>]
+
+A metadata file looks like
+  {\"name\": \"...\", \"symbol\": \"...\", \"uri\": \"https://...\",
+   \"seller_fee_basis_points\": 0, \"recipient\": \"<wallet>\"}
+where the last two fields are optional.  In batch mode every .json file of the
+folder is minted once, progress is logged to <folder>/minted.csv.  When the
+batch is run again, mints whose outcome wasn't known are looked up instead of
+minting a second NFT, and failed collection verifications are retried.";
+
+const MINTED_FILE: &str = "minted.csv";
+
+fn main() {
+    let args = Args::from_env();
+    let collection = args
+        .option("collection")
+        .map(|collection| parse_pubkey(collection, "collection"));
+    let is_collection = args.flag("as-collection");
+    let dry_run = args.flag("dry-run");
+    if is_collection && collection.is_some() {
+        fail("A collection NFT cannot be part of another collection here");
+    }
+
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+    let payer = load_keypair();
+
+    match args.option("batch") {
+        Some(folder) => batch(
+            &rpc_client,
+            &payer,
+            Path::new(folder),
+            collection.as_ref(),
+            dry_run,
+        ),
+        None => {
+            let path = Path::new(args.required(0, USAGE));
+            let metadata = NftMetadata::from_file(path)
+                .unwrap_or_else(|err| fail(&format!("Failed to read {}: {}", path.display(), err)));
+            let mint = mint(
+                &rpc_client,
+                &payer,
+                &metadata,
+                collection.as_ref(),
+                is_collection,
+                dry_run,
+                |_, _, _| {},
+            )
+            .unwrap_or_else(|err| fail(&format!("Failed to mint {}: {}", metadata.name, err)));
+            if let (Some(mint), Some(collection)) = (mint, collection) {
+                // The NFT exists either way, a failure is only reported.
+                match verify(&rpc_client, &payer, &mint, &collection, dry_run) {
+                    Ok(()) | Err(SendError::DryRun) => {}
+                    Err(err) => eprintln!(
+                        "❌ Failed to verify {} in collection {}: {}",
+                        mint, collection, err
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// Progress of one file of a batch, as logged in [`MINTED_FILE`].  Rows are
+/// `<file>,<status>,<mint>[,<signature>,<last_valid_block_height>]`.
+enum Entry {
+    /// A mint attempt, recorded before it is sent.
+    Sending {
+        mint: Pubkey,
+        signature: Signature,
+        last_valid_block_height: u64,
+    },
+    /// Minted, outside of a collection.  Rows of older versions, only
+    /// `<file>,<mint>`, mean the same.
+    Minted,
+    /// Minted into a collection, but not verified in it yet.
+    Unverified(Pubkey),
+    Verified,
+}
+
+/// What is left to do for one file of a batch.
+enum Progress<'a> {
+    New,
+    Done,
+    /// Mint attempts whose outcome wasn't known.
+    Sent(Vec<&'a Entry>),
+    Unverified(Pubkey),
+}
+
+fn progress(entries: &[Entry]) -> Progress<'_> {
+    if entries
+        .iter()
+        .any(|entry| matches!(entry, Entry::Minted | Entry::Verified))
+    {
+        return Progress::Done;
+    }
+    if let Some(mint) = entries.iter().find_map(|entry| match entry {
+        Entry::Unverified(mint) => Some(*mint),
+        _ => None,
+    }) {
+        return Progress::Unverified(mint);
+    }
+    match entries.is_empty() {
+        true => Progress::New,
+        false => Progress::Sent(entries.iter().collect()),
+    }
+}
+
+/// Reads the log of a batch, the entries of every file in order.
+fn parse_log(contents: &str) -> Result<HashMap<String, Vec<Entry>>, String> {
+    let mut log: HashMap<String, Vec<Entry>> = HashMap::new();
+    for (index, line) in contents.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = || format!("invalid row on line {}", index + 1);
+        let fields: Vec<&str> = line.split(',').collect();
+        let pubkey = |field: &str| Pubkey::from_str(field).map_err(|_| invalid());
+        let entry = match fields[1..] {
+            [mint] | ["minted", mint] => pubkey(mint).map(|_| Entry::Minted)?,
+            ["unverified", mint] => Entry::Unverified(pubkey(mint)?),
+            ["verified", mint] => pubkey(mint).map(|_| Entry::Verified)?,
+            ["sending", mint, signature, last_valid_block_height] => Entry::Sending {
+                mint: pubkey(mint)?,
+                signature: Signature::from_str(signature).map_err(|_| invalid())?,
+                last_valid_block_height: last_valid_block_height.parse().map_err(|_| invalid())?,
+            },
+            _ => return Err(invalid()),
+        };
+        log.entry(fields[0].to_string()).or_default().push(entry);
+    }
+    Ok(log)
+}
+
+fn batch(
+    rpc_client: &RpcClient,
+    payer: &impl Signer,
+    folder: &Path,
+    collection: Option<&Pubkey>,
+    dry_run: bool,
+) {
+    let minted_path = folder.join(MINTED_FILE);
+    let previous = fs::read_to_string(&minted_path).unwrap_or_default();
+    let previous = parse_log(&previous).unwrap_or_else(|err| {
+        fail(&format!(
+            "Failed to read {}: {}",
+            minted_path.display(),
+            err
+        ))
+    });
+
+    let mut files: Vec<PathBuf> = fs::read_dir(folder)
+        .unwrap_or_else(|err| fail(&format!("Failed to read {}: {}", folder.display(), err)))
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    files.sort();
+
+    // Mint attempts of earlier runs that may have landed are looked up
+    // instead of minting those files again.
+    let attempts: Vec<(Signature, u64)> = previous
+        .values()
+        .filter_map(|entries| match progress(entries) {
+            Progress::Sent(entries) => Some(entries),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|entry| match entry {
+            Entry::Sending {
+                signature,
+                last_valid_block_height,
+                ..
+            } => Some((*signature, *last_valid_block_height)),
+            _ => None,
+        })
+        .collect();
+    let states = check_attempts(rpc_client, &attempts)
+        .unwrap_or_else(|err| fail(&format!("Failed to check earlier attempts: {}", err)));
+
+    let mut log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&minted_path)
+        .unwrap_or_else(|err| {
+            fail(&format!(
+                "Failed to open {}: {}",
+                minted_path.display(),
+                err
+            ))
+        });
+
+    let (mut succeeded, mut failed, mut unknown) = (0, 0, 0);
+    for path in files {
+        let file_name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let entries = previous
+            .get(&file_name)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let minted = match progress(entries) {
+            Progress::Done => {
+                println!("⏭️  {} was already minted", file_name);
+                continue;
+            }
+            Progress::New => None,
+            Progress::Unverified(mint) => Some(mint),
+            Progress::Sent(entries) => {
+                let attempts = entries.iter().filter_map(|entry| match entry {
+                    Entry::Sending {
+                        mint, signature, ..
+                    } => states.get(signature).map(|state| (mint, signature, state)),
+                    _ => None,
+                });
+                match attempts.max_by_key(|(_, _, state)| **state) {
+                    Some((mint, signature, Attempt::Landed)) => {
+                        println!(
+                            "✅ {} was minted earlier as {}: {}",
+                            file_name, mint, signature
+                        );
+                        record_minted(&mut log, &file_name, mint, collection);
+                        Some(*mint)
+                    }
+                    Some((mint, signature, Attempt::InFlight)) => {
+                        println!(
+                            "⏳ {} may still be minted as {}: {}, run again later",
+                            file_name, mint, signature
+                        );
+                        continue;
+                    }
+                    _ => None,
+                }
+            }
+        };
+        let minted = match minted {
+            Some(mint) => mint,
+            None => match mint_file(rpc_client, payer, &path, collection, dry_run, &mut log) {
+                Ok(Some(mint)) => mint,
+                Ok(None) => continue,
+                Err(MintError::Unknown(err)) => {
+                    eprintln!(
+                        "❓ {} may have been minted, run again to check: {}",
+                        file_name, err
+                    );
+                    unknown += 1;
+                    continue;
+                }
+                Err(MintError::Failed(err)) => {
+                    eprintln!("❌ Failed to mint {}: {}", file_name, err);
+                    failed += 1;
+                    continue;
+                }
+            },
+        };
+
+        // The NFT exists, only its verification is retried when it fails.
+        let Some(collection) = collection else {
+            succeeded += 1;
+            continue;
+        };
+        match verify(rpc_client, payer, &minted, collection, dry_run) {
+            Ok(()) => {
+                writeln!(log, "{},verified,{}", file_name, minted).unwrap();
+                succeeded += 1;
+            }
+            Err(SendError::DryRun) => {}
+            Err(err) => {
+                eprintln!(
+                    "❌ Failed to verify {} in collection {}, run again to retry: {}",
+                    minted, collection, err
+                );
+                writeln!(log, "{},unverified,{}", file_name, minted).unwrap();
+                failed += 1;
+            }
+        }
+    }
+    if dry_run {
+        return;
+    }
+
+    println!(
+        "Minted {} NFTs, {} failed, {} unknown",
+        succeeded, failed, unknown
+    );
+}
+
+/// Why a file of a batch wasn't minted.
+enum MintError {
+    Failed(Box<dyn Error>),
+    /// The mint was sent, but whether it landed isn't known.
+    Unknown(Box<dyn Error>),
+}
+
+/// Mints the NFT of one file of a batch, logging every attempt before it is
+/// sent and the mint once it landed.  `None` on a dry run.
+fn mint_file(
+    rpc_client: &RpcClient,
+    payer: &impl Signer,
+    path: &Path,
+    collection: Option<&Pubkey>,
+    dry_run: bool,
+    log: &mut File,
+) -> Result<Option<Pubkey>, MintError> {
+    let file_name = path.file_name().unwrap().to_string_lossy();
+    let metadata = NftMetadata::from_file(path).map_err(MintError::Failed)?;
+
+    let mut signed = false;
+    let result = mint(
+        rpc_client,
+        payer,
+        &metadata,
+        collection,
+        false,
+        dry_run,
+        |mint, signature, last_valid_block_height| {
+            signed = true;
+            writeln!(
+                log,
+                "{},sending,{},{},{}",
+                file_name, mint, signature, last_valid_block_height
+            )
+            .unwrap();
+        },
+    );
+    match result {
+        Ok(Some(mint)) => {
+            record_minted(log, &file_name, &mint, collection);
+            Ok(Some(mint))
+        }
+        Ok(None) => Ok(None),
+        // Only a mint that landed and failed is known not to exist.
+        Err(err)
+            if signed
+                && !matches!(
+                    err.downcast_ref::<SendError>(),
+                    Some(SendError::Transaction(_))
+                ) =>
+        {
+            Err(MintError::Unknown(err))
+        }
+        Err(err) => Err(MintError::Failed(err)),
+    }
+}
+
+/// Logs a landed mint, which still has to be verified when it belongs to a
+/// collection.
+fn record_minted(log: &mut File, file_name: &str, mint: &Pubkey, collection: Option<&Pubkey>) {
+    let status = match collection {
+        Some(_) => "unverified",
+        None => "minted",
+    };
+    writeln!(log, "{},{},{}", file_name, status, mint).unwrap();
+}
+
+/// Mints one NFT and returns its mint address, `None` on a dry run.
+/// `on_signed` gets the mint, the signature and the last valid block height
+/// of every attempt before it is sent.
+fn mint(
+    rpc_client: &RpcClient,
+    payer: &impl Signer,
+    metadata: &NftMetadata,
+    collection: Option<&Pubkey>,
+    is_collection: bool,
+    dry_run: bool,
+    mut on_signed: impl FnMut(&Pubkey, &Signature, u64),
+) -> Result<Option<Pubkey>, Box<dyn Error>> {
+    let mint_keypair = Keypair::new();
+    let recipient = metadata.recipient()?.unwrap_or(payer.pubkey());
+    let mint_rent = rpc_client.get_minimum_balance_for_rent_exemption(Mint::LEN)?;
+
+    let instructions = mint_nft_instructions(
+        &payer.pubkey(),
+        &mint_keypair.pubkey(),
+        &recipient,
+        metadata,
+        mint_rent,
+        collection,
+        is_collection,
+    );
+    let signature = match TransactionSender::new(rpc_client, dry_run).send_tracked(
+        &instructions,
+        &payer.pubkey(),
+        &[payer as &dyn Signer, &mint_keypair],
+        |signature, last_valid_block_height| {
+            on_signed(&mint_keypair.pubkey(), signature, last_valid_block_height)
+        },
+    ) {
+        Ok(signature) => signature,
+        Err(SendError::DryRun) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    println!("✅ Minted {}: {}", metadata.name, mint_keypair.pubkey());
+    println!("Transaction Signature: {}", signature);
+    println!(
+        "🌐 Explorer Link: https://explorer.solana.com/address/{}?cluster=devnet",
+        mint_keypair.pubkey()
+    );
+    Ok(Some(mint_keypair.pubkey()))
+}
+
+/// Verifies a minted NFT in its collection.  This is a separate transaction,
+/// together with the mint it would not fit the size limit with long names
+/// and URIs.
+fn verify(
+    rpc_client: &RpcClient,
+    payer: &impl Signer,
+    mint: &Pubkey,
+    collection: &Pubkey,
+    dry_run: bool,
+) -> Result<(), SendError> {
+    let instruction = verify_collection_instruction(&payer.pubkey(), mint, collection);
+    let signature = TransactionSender::new(rpc_client, dry_run).send(
+        &[instruction],
+        &payer.pubkey(),
+        &[payer],
+    )?;
+    println!("✅ Verified {} in collection {}", mint, collection);
+    println!("Transaction Signature: {}", signature);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_rows_of_older_versions_as_minted() {
+        let mint = Pubkey::new_unique();
+        let log = parse_log(&format!("a.json,{}\n", mint)).unwrap();
+        assert!(matches!(progress(&log["a.json"]), Progress::Done));
+    }
+
+    #[test]
+    fn tracks_progress_of_a_file() {
+        let mint = Pubkey::new_unique();
+        let signature = Signature::from([1; 64]);
+        let sending = format!("a.json,sending,{},{},100\n", mint, signature);
+        let unverified = format!("a.json,unverified,{}\n", mint);
+        let verified = format!("a.json,verified,{}\n", mint);
+
+        let log = parse_log(&sending).unwrap();
+        let Progress::Sent(entries) = progress(&log["a.json"]) else {
+            panic!("the mint was only sent");
+        };
+        assert!(matches!(
+            entries[..],
+            [Entry::Sending {
+                last_valid_block_height: 100,
+                ..
+            }]
+        ));
+
+        let log = parse_log(&(sending.clone() + &unverified)).unwrap();
+        assert!(matches!(
+            progress(&log["a.json"]),
+            Progress::Unverified(unverified) if unverified == mint
+        ));
+
+        let log = parse_log(&(sending + &unverified + &verified)).unwrap();
+        assert!(matches!(progress(&log["a.json"]), Progress::Done));
+        assert!(matches!(progress(&[]), Progress::New));
+    }
+
+    #[test]
+    fn rejects_malformed_rows() {
+        assert!(parse_log("a.json").is_err());
+        assert!(parse_log("a.json,minted,nope").is_err());
+        assert!(parse_log("a.json,sending,11111111111111111111111111111111,sig,1").is_err());
+        assert!(parse_log("a.json,burned,11111111111111111111111111111111").is_err());
+    }
+}
@@ -1,12 +1,16 @@
+use scripts_2_1_2_6::cli::Args;
 use scripts_2_1_2_6::load_keypair;
+use scripts_2_1_2_6::memo::memo_instruction;
+use scripts_2_1_2_6::send::{SendError, TransactionSender};
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::{pubkey::Pubkey, signature::Signer, transaction::Transaction};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
 use spl_token::instruction::mint_to;
 use std::str::FromStr;
 
 fn main() {
     const MINOR_UNITS_PER_MAJOR_UNITS: u64 = 10_u64.pow(2);
 
+    let args = Args::from_env();
     let rpc_client = RpcClient::new("https://api.devnet.solana.com");
     let mint = Pubkey::from_str("EbqXNvEBmVox6e85Z6UBCFeCZDpYXHgkBEF9LK139kzu").unwrap();
     let recipient_token_account =
@@ -24,25 +28,24 @@ fn main() {
     )
     .unwrap();
 
-    // Fetch the latest blockhash
-    let blockhash = rpc_client.get_latest_blockhash().unwrap();
+    let mut instructions = vec![instruction];
+    if let Some(memo) = args.option("memo") {
+        instructions.push(memo_instruction(memo, &[&mint_authority.pubkey()]));
+    }
 
-    // Build and sign the transaction
-    let transaction = Transaction::new_signed_with_payer(
-        &[instruction],                 // Instruction to mint tokens
-        Some(&mint_authority.pubkey()), // Payer of the transaction fee
-        &[mint_authority],              // Signers (payer and mint authority)
-        blockhash,
-    );
-
-    // Send and confirm the transaction
-    match rpc_client.send_and_confirm_transaction(&transaction) {
+    // Simulate, sign, send and confirm the transaction
+    match TransactionSender::new(&rpc_client, args.flag("dry-run")).send(
+        &instructions,            // Instructions to mint tokens and attach the memo
+        &mint_authority.pubkey(), // Payer of the transaction fee
+        &[&mint_authority],       // Signers (payer and mint authority)
+    ) {
         Ok(signature) => {
             println!("✅ Tokens minted successfully!");
             println!("Transaction Signature: {}", signature);
         }
+        Err(SendError::DryRun) => {}
         Err(err) => {
-            eprintln!("❌ Failed to mint tokens: {:?}", err);
+            eprintln!("❌ Failed to mint tokens: {}", err);
         }
     };
 }
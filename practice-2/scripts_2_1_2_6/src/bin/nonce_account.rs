@@ -0,0 +1,272 @@
+use scripts_2_1_2_6::cli::{Args, fail, parse, parse_pubkey};
+use scripts_2_1_2_6::nonce::{
+    build_durable_transaction, durable_nonce_account, get_nonce_data, missing_signers,
+};
+use scripts_2_1_2_6::send::{SendError, TransactionSender, dry_run};
+use scripts_2_1_2_6::{decode_transaction, encode_transaction, load_keypair};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    native_token::lamports_to_sol,
+    nonce::state::State as NonceState,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use std::fs;
+
+const USAGE: &str = "Usage: nonce_account <command>
+
+Commands:
+  create [--authority=<pubkey>]
+  show <nonce_account>
+  advance <nonce_account>
+  authorize <nonce_account> <new_authority>
+  withdraw <nonce_account> <recipient> <lamports>
+  build-transfer <nonce_account> <recipient> <lamports> <tx_file> [--payer=<pubkey>]
+  sign <tx_file>
+  submit <tx_file>";
+
+fn main() {
+    let args = Args::from_env();
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+
+    match args.required(0, USAGE) {
+        "create" => create(&rpc_client, &args),
+        "show" => show(&rpc_client, &args),
+        "advance" => advance(&rpc_client, &args),
+        "authorize" => authorize(&rpc_client, &args),
+        "withdraw" => withdraw(&rpc_client, &args),
+        "build-transfer" => build_transfer(&rpc_client, &args),
+        "sign" => sign(&args),
+        "submit" => submit(&rpc_client, &args),
+        _ => fail(USAGE),
+    }
+}
+
+fn create(rpc_client: &RpcClient, args: &Args) {
+    let payer = load_keypair();
+    let nonce_keypair = Keypair::new();
+    let authority = args
+        .option("authority")
+        .map(|authority| parse_pubkey(authority, "authority"))
+        .unwrap_or_else(|| payer.pubkey());
+
+    let rent_exemption = rpc_client
+        .get_minimum_balance_for_rent_exemption(NonceState::size())
+        .unwrap();
+
+    let instructions = system_instruction::create_nonce_account(
+        &payer.pubkey(),
+        &nonce_keypair.pubkey(),
+        &authority,
+        rent_exemption,
+    );
+
+    match TransactionSender::new(rpc_client, args.flag("dry-run")).send(
+        &instructions,
+        &payer.pubkey(),
+        &[&payer, &nonce_keypair as &dyn Signer],
+    ) {
+        Ok(signature) => {
+            println!("✅ Nonce account created: {}", nonce_keypair.pubkey());
+            println!("Authority: {}", authority);
+            println!("Transaction Signature: {}", signature);
+        }
+        Err(SendError::DryRun) => {}
+        Err(err) => eprintln!("❌ Failed to create nonce account: {}", err),
+    }
+}
+
+fn show(rpc_client: &RpcClient, args: &Args) {
+    let nonce_account = parse_pubkey(args.required(1, USAGE), "nonce account");
+
+    match get_nonce_data(rpc_client, &nonce_account) {
+        Ok(data) => {
+            let balance = rpc_client.get_balance(&nonce_account).unwrap();
+            println!("Nonce account: {}", nonce_account);
+            println!("Balance: {} SOL", lamports_to_sol(balance));
+            println!("Authority: {}", data.authority);
+            println!("Durable nonce: {}", data.blockhash());
+            println!(
+                "Lamports per signature: {}",
+                data.get_lamports_per_signature()
+            );
+        }
+        Err(err) => eprintln!("❌ Failed to read nonce account: {}", err),
+    }
+}
+
+fn advance(rpc_client: &RpcClient, args: &Args) {
+    let nonce_account = parse_pubkey(args.required(1, USAGE), "nonce account");
+    let authority = load_keypair();
+
+    let instruction =
+        system_instruction::advance_nonce_account(&nonce_account, &authority.pubkey());
+    send(rpc_client, args, instruction, &authority, "Nonce advanced");
+
+    if let Ok(data) = get_nonce_data(rpc_client, &nonce_account) {
+        println!("New durable nonce: {}", data.blockhash());
+    }
+}
+
+fn authorize(rpc_client: &RpcClient, args: &Args) {
+    let nonce_account = parse_pubkey(args.required(1, USAGE), "nonce account");
+    let new_authority = parse_pubkey(args.required(2, USAGE), "new authority");
+    let authority = load_keypair();
+
+    let instruction = system_instruction::authorize_nonce_account(
+        &nonce_account,
+        &authority.pubkey(),
+        &new_authority,
+    );
+    send(
+        rpc_client,
+        args,
+        instruction,
+        &authority,
+        "Nonce authority changed",
+    );
+}
+
+fn withdraw(rpc_client: &RpcClient, args: &Args) {
+    let nonce_account = parse_pubkey(args.required(1, USAGE), "nonce account");
+    let recipient = parse_pubkey(args.required(2, USAGE), "recipient");
+    let lamports: u64 = parse(args.required(3, USAGE), "lamports");
+    let authority = load_keypair();
+
+    let instruction = system_instruction::withdraw_nonce_account(
+        &nonce_account,
+        &authority.pubkey(),
+        &recipient,
+        lamports,
+    );
+    send(
+        rpc_client,
+        args,
+        instruction,
+        &authority,
+        "Withdrawn from nonce account",
+    );
+}
+
+/// Builds an unsigned SOL transfer that relies on the durable nonce, so it
+/// can be carried to an offline machine, signed there and submitted later.
+fn build_transfer(rpc_client: &RpcClient, args: &Args) {
+    let nonce_account = parse_pubkey(args.required(1, USAGE), "nonce account");
+    let recipient = parse_pubkey(args.required(2, USAGE), "recipient");
+    let lamports: u64 = parse(args.required(3, USAGE), "lamports");
+    let tx_file = args.required(4, USAGE);
+    let payer = args
+        .option("payer")
+        .map(|payer| parse_pubkey(payer, "payer"))
+        .unwrap_or_else(|| load_keypair().pubkey());
+
+    let data = get_nonce_data(rpc_client, &nonce_account)
+        .unwrap_or_else(|err| fail(&format!("Failed to read nonce account: {}", err)));
+
+    let transaction = build_durable_transaction(
+        vec![system_instruction::transfer(&payer, &recipient, lamports)],
+        &payer,
+        &nonce_account,
+        &data.authority,
+        data.blockhash(),
+    );
+
+    fs::write(tx_file, encode_transaction(&transaction)).expect("Failed to write transaction file");
+
+    println!("✅ Unsigned durable transaction written to {}", tx_file);
+    println!("Durable nonce: {}", data.blockhash());
+    print_missing_signers(&transaction);
+}
+
+/// Adds our signature to a transaction file.  Does not need network access.
+fn sign(args: &Args) {
+    let tx_file = args.required(1, USAGE);
+    let mut transaction = read_transaction(tx_file);
+    let signer = load_keypair();
+
+    let recent_blockhash = transaction.message.recent_blockhash;
+    if let Err(err) = transaction.try_partial_sign(&[&signer], recent_blockhash) {
+        fail(&format!("Failed to sign transaction: {}", err));
+    }
+
+    fs::write(tx_file, encode_transaction(&transaction)).expect("Failed to write transaction file");
+
+    println!("✅ Signed by {}", signer.pubkey());
+    print_missing_signers(&transaction);
+}
+
+fn submit(rpc_client: &RpcClient, args: &Args) {
+    let tx_file = args.required(1, USAGE);
+    let transaction = read_transaction(tx_file);
+
+    let missing = missing_signers(&transaction)
+        .unwrap_or_else(|err| fail(&format!("Invalid transaction file: {}", err)));
+    if !missing.is_empty() {
+        print_missing_signers(&transaction);
+        fail("Transaction is not fully signed");
+    }
+
+    let nonce_account = durable_nonce_account(&transaction)
+        .unwrap_or_else(|err| fail(&format!("Invalid transaction file: {}", err)))
+        .unwrap_or_else(|| fail("Transaction does not start with advance_nonce_account"));
+    let data = get_nonce_data(rpc_client, &nonce_account)
+        .unwrap_or_else(|err| fail(&format!("Failed to read nonce account: {}", err)));
+    if data.blockhash() != transaction.message.recent_blockhash {
+        fail("Nonce has been advanced since the transaction was built");
+    }
+
+    // The transaction is already signed, it is simulated as it is.
+    if args.flag("dry-run") {
+        if let Err(err) = dry_run(rpc_client, &transaction.into()) {
+            fail(&err.to_string());
+        }
+        return;
+    }
+
+    match rpc_client.send_and_confirm_transaction(&transaction) {
+        Ok(signature) => {
+            println!("✅ Durable transaction submitted!");
+            println!("Transaction Signature: {}", signature);
+        }
+        Err(err) => eprintln!("❌ Failed to submit transaction: {:?}", err),
+    }
+}
+
+fn send(
+    rpc_client: &RpcClient,
+    args: &Args,
+    instruction: Instruction,
+    authority: &impl Signer,
+    success: &str,
+) {
+    let sender = TransactionSender::new(rpc_client, args.flag("dry-run"));
+    match sender.send(&[instruction], &authority.pubkey(), &[authority]) {
+        Ok(signature) => {
+            println!("✅ {}!", success);
+            println!("Transaction Signature: {}", signature);
+        }
+        Err(SendError::DryRun) => {}
+        Err(err) => eprintln!("❌ Failed: {}", err),
+    }
+}
+
+fn read_transaction(tx_file: &str) -> Transaction {
+    let encoded = fs::read_to_string(tx_file).expect("Failed to read transaction file");
+    decode_transaction(&encoded)
+        .unwrap_or_else(|err| fail(&format!("Invalid transaction file: {}", err)))
+}
+
+fn print_missing_signers(transaction: &Transaction) {
+    let missing: Vec<Pubkey> = missing_signers(transaction)
+        .unwrap_or_else(|err| fail(&format!("Invalid transaction file: {}", err)));
+    if missing.is_empty() {
+        println!("All signatures present, ready to submit.");
+    } else {
+        for signer in missing {
+            println!("Missing signature: {}", signer);
+        }
+    }
+}
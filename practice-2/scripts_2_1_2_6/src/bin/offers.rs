@@ -0,0 +1,76 @@
+use scripts_2_1_2_6::cli::{Args, fail, parse_pubkey};
+use scripts_2_1_2_6::escrow::get_offers_for_taker;
+use scripts_2_1_2_6::get_pubkey;
+use scripts_2_1_2_6::pay::format_request_amount;
+use scripts_2_1_2_6::token::{format_amount, get_mints};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const USAGE: &str = "Usage: offers [<taker>]
+
+Lists the open escrow offers that only <taker>, or our wallet, may take.";
+
+fn main() {
+    let args = Args::from_env();
+    if args.flag("help") {
+        fail(USAGE);
+    }
+    let taker = parse_pubkey(args.positional(0).unwrap_or(&get_pubkey()), "taker");
+
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+
+    let mut offers = Vec::new();
+    for offer in get_offers_for_taker(&rpc_client, &taker)
+        .unwrap_or_else(|err| fail(&format!("Failed to read offers: {}", err)))
+    {
+        match offer {
+            Ok(offer) => offers.push(offer),
+            Err(err) => eprintln!("⏭️  Skipped an account: {}", err),
+        }
+    }
+    if offers.is_empty() {
+        println!("No offers addressed to {}", taker);
+        return;
+    }
+
+    let mut addresses: Vec<Pubkey> = offers
+        .iter()
+        .flat_map(|offer| [offer.token_mint_a, offer.token_mint_b])
+        .filter(|mint| *mint != Pubkey::default())
+        .collect();
+    addresses.sort();
+    addresses.dedup();
+    let mints = get_mints(&rpc_client, &addresses)
+        .unwrap_or_else(|err| fail(&format!("Failed to read mints: {}", err)));
+    let format = |amount: u64, mint: &Pubkey| match mints.iter().find(|info| info.address == *mint)
+    {
+        Some(info) => format!("{} of {}", format_amount(amount, info.mint.decimals), mint),
+        None => format!("{} of {}", amount, mint),
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64);
+    println!("Offers addressed to {}:", taker);
+    for offer in &offers {
+        let expiry = match offer.expires_at {
+            Some(_) if offer.is_expired(now) => " (expired)".to_string(),
+            Some(expires_at) => format!(" (expires at {})", expires_at),
+            None => String::new(),
+        };
+        let wanted = if offer.wants_sol() {
+            format_request_amount(offer.token_b_remaining_amount, None)
+        } else {
+            format(offer.token_b_remaining_amount, &offer.token_mint_b)
+        };
+        println!(
+            "{} by {}: {} for {}{}",
+            offer.address,
+            offer.maker,
+            format(offer.token_a_remaining_amount, &offer.token_mint_a),
+            wanted,
+            expiry
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use scripts_2_1_2_6::cli::{Args, fail, parse, parse_pubkey};
+use scripts_2_1_2_6::memo::read_memos;
+use solana_client::rpc_client::RpcClient;
+
+const USAGE: &str = "Usage: read_memos <address> [--limit=<count>] [--contains=<text>]";
+
+fn main() {
+    let args = Args::from_env();
+    let address = parse_pubkey(args.required(0, USAGE), "address");
+    let limit: usize = args
+        .option("limit")
+        .map(|limit| parse(limit, "limit"))
+        .unwrap_or(100);
+    // Lets us look up a single invoice, for example `--contains=INV-42`.
+    let contains = args.option("contains");
+
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+
+    let memos = read_memos(&rpc_client, &address, limit)
+        .unwrap_or_else(|err| fail(&format!("Failed to read memos: {}", err)));
+
+    let mut found = 0;
+    for entry in memos {
+        let memos: Vec<&String> = entry
+            .memos
+            .iter()
+            .filter(|memo| contains.is_none_or(|text| memo.contains(text)))
+            .collect();
+        if memos.is_empty() {
+            continue;
+        }
+        found += 1;
+
+        let status = if entry.failed { "❌" } else { "✅" };
+        let time = entry
+            .block_time
+            .map(|time| time.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        println!(
+            "{} {} (slot {}, block time {})",
+            status, entry.signature, entry.slot, time
+        );
+        for memo in memos {
+            println!("    {}", memo);
+        }
+    }
+
+    println!(
+        "Found {} transactions with matching memos for {}",
+        found, address
+    );
+}
@@ -1,46 +1,547 @@
+use scripts_2_1_2_6::cli::{Args, fail};
 use scripts_2_1_2_6::load_keypair;
+use scripts_2_1_2_6::memo::memo_instruction;
+use scripts_2_1_2_6::send::{
+    Attempt, MAX_COMPUTE_UNIT_LIMIT, SendError, TransactionSender, check_attempts,
+    with_compute_budget,
+};
+use scripts_2_1_2_6::token::{format_amount, parse_ui_amount};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
-    instruction::Instruction, pubkey::Pubkey, signature::Signer, system_instruction,
-    transaction::Transaction,
+    instruction::Instruction, message::Message, native_token::lamports_to_sol, pubkey::Pubkey,
+    signature::Signature, signature::Signer, system_instruction,
 };
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+const USAGE: &str = "Usage: sendsol [--memo=<text>]
+       sendsol --batch=<payouts.csv>
+
+In batch mode every row of the CSV file is a payout:
+  <recipient>,<amount_sol>[,<memo>]
+A header row and empty lines are skipped.  Results are appended to
+<payouts>.results.csv, rows recorded there as sent are skipped when the batch
+is run again, and transactions whose outcome wasn't known are looked up
+instead of being sent again.";
+
+/// Largest serialized transaction the network accepts.
+const MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// Upper bound of payouts per transaction, even when more would fit.
+const MAX_PAYOUTS_PER_TRANSACTION: usize = 10;
+
+/// Fee of one signature, used to check the balance before starting.
+const LAMPORTS_PER_SIGNATURE: u64 = 5000;
+
 pub fn main() {
+    let args = Args::from_env();
     let sender_keypair = load_keypair();
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+
+    if args.flag("batch") {
+        let csv = args.option("batch").unwrap_or_else(|| fail(USAGE));
+        batch(
+            &rpc_client,
+            &sender_keypair,
+            Path::new(csv),
+            args.flag("dry-run"),
+        );
+        return;
+    }
+
     let rec = Pubkey::from_str("8Wy8nY4QNQoFzRf8kff5FFnJR3LmGmpJpMN2GPrwWMrf")
         .expect("Invalid recipient key");
-    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
-    let blockhash = rpc_client.get_latest_blockhash().unwrap();
-    let memo_program = Pubkey::from_str("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr")
-        .expect("Invalid memo program key");
-    let memo_text = "Hello from Rust!";
+    let memo_text = args.option("memo").unwrap_or("Hello from Rust!");
 
     let transfer_instruction =
         system_instruction::transfer(&sender_keypair.pubkey(), &rec, 5_000_000);
 
-    // Create the memo instruction
-    let memo_instruction = Instruction {
-        program_id: memo_program,
-        accounts: vec![solana_sdk::instruction::AccountMeta::new(
-            sender_keypair.pubkey(),
-            true,
-        )],
-        data: memo_text.as_bytes().to_vec(),
-    };
-
-    let transaction = Transaction::new_signed_with_payer(
+    // Create the memo instruction, signed by the sender
+    let memo_instruction = memo_instruction(memo_text, &[&sender_keypair.pubkey()]);
+
+    let result = TransactionSender::new(&rpc_client, args.flag("dry-run")).send(
         &[transfer_instruction, memo_instruction],
-        Some(&sender_keypair.pubkey()),
-        &[sender_keypair],
-        blockhash,
+        &sender_keypair.pubkey(),
+        &[&sender_keypair],
     );
-
-    let result = rpc_client.send_and_confirm_transaction(&transaction);
     match result {
         // unlike TypeScript lib, transaction in Rust returns only transaction code
         // to print link, use something like: format!("https://explorer.solana.com/tx/{}?cluster=devnet", signature)
         Ok(signature) => println!("Transaction successful! Signature: {:?}", signature),
-        Err(e) => eprintln!("Error: {:?}", e),
+        Err(SendError::DryRun) => {}
+        Err(e) => eprintln!("Error: {}", e),
+    }
+}
+
+/// One row of a payout file.
+struct Payout {
+    line: usize,
+    recipient: Pubkey,
+    lamports: u64,
+    memo: Option<String>,
+    /// Counts identical rows, paying someone the same amount twice takes two
+    /// rows.
+    occurrence: usize,
+}
+
+impl Payout {
+    fn instructions(&self, sender: &Pubkey) -> Vec<Instruction> {
+        let mut instructions = vec![system_instruction::transfer(
+            sender,
+            &self.recipient,
+            self.lamports,
+        )];
+        if let Some(memo) = &self.memo {
+            instructions.push(memo_instruction(memo, &[sender]));
+        }
+        instructions
+    }
+
+    /// Key of the row in the results file.  It doesn't depend on the line
+    /// number, so rows can be added or removed between runs.  The memo comes
+    /// last as it may contain commas.
+    fn key(&self) -> String {
+        format!(
+            "{},{},{},{}",
+            self.recipient,
+            format_amount(self.lamports, 9),
+            self.occurrence,
+            self.memo.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+/// An outcome recorded in the results file.
+enum Outcome {
+    Sent,
+    Failed,
+    /// Sent, but whether it landed isn't known yet.
+    Unknown {
+        signature: Signature,
+        last_valid_block_height: u64,
+    },
+}
+
+fn batch(rpc_client: &RpcClient, sender: &impl Signer, csv: &Path, dry_run: bool) {
+    let contents = fs::read_to_string(csv)
+        .unwrap_or_else(|err| fail(&format!("Failed to read {}: {}", csv.display(), err)));
+    let payouts = parse_payouts(&contents).unwrap_or_else(|err| fail(&err));
+
+    let results_path = results_path(csv);
+    let previous_results = fs::read_to_string(&results_path).unwrap_or_default();
+    let previous = parse_results(&previous_results).unwrap_or_else(|err| {
+        fail(&format!(
+            "Failed to read {}: {}",
+            results_path.display(),
+            err
+        ))
+    });
+    let mut results = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&results_path)
+        .unwrap_or_else(|err| {
+            fail(&format!(
+                "Failed to open {}: {}",
+                results_path.display(),
+                err
+            ))
+        });
+
+    // Attempts of earlier runs that may have landed are looked up instead of
+    // paying those rows again.
+    let mut attempts: Vec<(Signature, u64)> = payouts
+        .iter()
+        .filter_map(|payout| previous.get(&payout.key()))
+        .filter(|outcomes| {
+            !outcomes
+                .iter()
+                .any(|outcome| matches!(outcome, Outcome::Sent))
+        })
+        .flatten()
+        .filter_map(|outcome| match outcome {
+            Outcome::Unknown {
+                signature,
+                last_valid_block_height,
+            } => Some((*signature, *last_valid_block_height)),
+            _ => None,
+        })
+        .collect();
+    // Rows paid by the same transaction share its signature.
+    attempts.sort();
+    attempts.dedup();
+    let states = check_attempts(rpc_client, &attempts)
+        .unwrap_or_else(|err| fail(&format!("Failed to check earlier attempts: {}", err)));
+
+    let mut pending = Vec::new();
+    for payout in &payouts {
+        let outcomes = previous
+            .get(&payout.key())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if outcomes
+            .iter()
+            .any(|outcome| matches!(outcome, Outcome::Sent))
+        {
+            println!(
+                "⏭️  Row {} to {} was already sent",
+                payout.line, payout.recipient
+            );
+            continue;
+        }
+        let attempts = outcomes.iter().filter_map(|outcome| match outcome {
+            Outcome::Unknown { signature, .. } => {
+                states.get(signature).map(|state| (signature, state))
+            }
+            _ => None,
+        });
+        match attempts.max_by_key(|(_, state)| **state) {
+            Some((signature, Attempt::Landed)) => {
+                println!(
+                    "✅ Row {} to {} landed earlier: {}",
+                    payout.line, payout.recipient, signature
+                );
+                writeln!(results, "sent,{},,{}", signature, payout.key()).unwrap();
+            }
+            Some((signature, Attempt::InFlight)) => println!(
+                "⏳ Row {} to {} may still land: {}, run again later",
+                payout.line, payout.recipient, signature
+            ),
+            _ => pending.push(payout),
+        }
+    }
+    if pending.is_empty() {
+        println!("Nothing left to pay");
+        return;
+    }
+
+    let batches = pack(&pending, &sender.pubkey()).unwrap_or_else(|err| fail(&err));
+    let total: u64 = pending.iter().map(|payout| payout.lamports).sum();
+    let fees = batches.len() as u64 * LAMPORTS_PER_SIGNATURE;
+    let balance = rpc_client
+        .get_balance(&sender.pubkey())
+        .unwrap_or_else(|err| fail(&format!("Failed to get balance: {}", err)));
+    println!(
+        "Paying {} SOL to {} recipients in {} transactions, balance {} SOL",
+        lamports_to_sol(total),
+        pending.len(),
+        batches.len(),
+        lamports_to_sol(balance)
+    );
+    if balance < total + fees {
+        fail(&format!(
+            "Balance too low, {} SOL needed including fees",
+            lamports_to_sol(total + fees)
+        ));
+    }
+
+    let sender_client = TransactionSender::new(rpc_client, dry_run);
+    let (mut paid, mut failed, mut unknown) = (0, 0, 0);
+    for batch in batches {
+        let instructions: Vec<Instruction> = batch
+            .iter()
+            .flat_map(|payout| payout.instructions(&sender.pubkey()))
+            .collect();
+
+        // Every attempt is recorded before it is sent, so a crash or an RPC
+        // error while confirming can't make the next run pay these rows
+        // again.  A failed transaction pays nobody in it, so every row shares
+        // the outcome.
+        let mut signed = false;
+        let result = sender_client.send_tracked(
+            &instructions,
+            &sender.pubkey(),
+            &[sender],
+            |signature, last_valid_block_height| {
+                signed = true;
+                for payout in &batch {
+                    writeln!(
+                        results,
+                        "unknown,{},{},{}",
+                        signature,
+                        last_valid_block_height,
+                        payout.key()
+                    )
+                    .unwrap();
+                }
+            },
+        );
+        let outcome = match result {
+            Ok(signature) => {
+                println!("✅ Paid {} recipients: {}", batch.len(), signature);
+                paid += batch.len();
+                format!("sent,{},", signature)
+            }
+            Err(SendError::DryRun) => continue,
+            // Only a transaction that landed and failed, or one that never
+            // went out, is known not to have paid.
+            Err(err) if signed && !matches!(err, SendError::Transaction(_)) => {
+                eprintln!(
+                    "❓ Payment to {} recipients may have landed, run again to check: {}",
+                    batch.len(),
+                    err
+                );
+                unknown += batch.len();
+                continue;
+            }
+            Err(err) => {
+                eprintln!("❌ Failed to pay {} recipients: {}", batch.len(), err);
+                failed += batch.len();
+                // Commas would break the columns of the results file.
+                format!("failed,{},", err.to_string().replace([',', '\n'], " "))
+            }
+        };
+        for payout in batch {
+            writeln!(results, "{},{}", outcome, payout.key()).unwrap();
+        }
+    }
+    if sender_client.is_dry_run() {
+        return;
+    }
+
+    println!(
+        "Paid {} recipients, {} failed, {} unknown, results in {}",
+        paid,
+        failed,
+        unknown,
+        results_path.display()
+    );
+}
+
+fn parse_payouts(contents: &str) -> Result<Vec<Payout>, String> {
+    let mut payouts = Vec::new();
+    let mut occurrences: HashMap<(Pubkey, u64, Option<&str>), usize> = HashMap::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        // The memo is the rest of the line, so it may contain commas.
+        let mut fields = line.splitn(3, ',').map(str::trim);
+        let recipient = fields.next().unwrap_or_default();
+        let amount = fields.next().unwrap_or_default();
+        let memo = fields.next().filter(|memo| !memo.is_empty());
+
+        let Ok(recipient) = Pubkey::from_str(recipient) else {
+            if line_number == 1 {
+                // Header row
+                continue;
+            }
+            return Err(format!(
+                "Invalid recipient on line {}: {}",
+                line_number, recipient
+            ));
+        };
+        let lamports = parse_ui_amount(amount, 9)
+            .filter(|lamports| *lamports > 0)
+            .ok_or_else(|| format!("Invalid amount on line {}: {}", line_number, amount))?;
+
+        let occurrence = occurrences.entry((recipient, lamports, memo)).or_default();
+        *occurrence += 1;
+        payouts.push(Payout {
+            line: line_number,
+            recipient,
+            lamports,
+            memo: memo.map(str::to_string),
+            occurrence: *occurrence,
+        });
+    }
+    Ok(payouts)
+}
+
+/// Reads the results file, the outcomes of every payout by its key.  Rows are
+/// `<sent|failed|unknown>,<signature|error>,<last_valid_block_height>,<key>`,
+/// the block height is only set for unknown rows.
+fn parse_results(contents: &str) -> Result<HashMap<String, Vec<Outcome>>, String> {
+    let mut results: HashMap<String, Vec<Outcome>> = HashMap::new();
+    for (index, line) in contents.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = || format!("invalid row on line {}", index + 1);
+        let [status, detail, block_height, key] = line
+            .splitn(4, ',')
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|_| invalid())?;
+        let outcome = match status {
+            "sent" => Outcome::Sent,
+            "failed" => Outcome::Failed,
+            "unknown" => Outcome::Unknown {
+                signature: Signature::from_str(detail).map_err(|_| invalid())?,
+                last_valid_block_height: block_height.parse().map_err(|_| invalid())?,
+            },
+            _ => return Err(invalid()),
+        };
+        results.entry(key.to_string()).or_default().push(outcome);
+    }
+    Ok(results)
+}
+
+/// Groups payouts into transactions that stay under the size limit.
+fn pack<'a>(payouts: &[&'a Payout], sender: &Pubkey) -> Result<Vec<Vec<&'a Payout>>, String> {
+    let mut batches: Vec<Vec<&Payout>> = Vec::new();
+    let mut current: Vec<&Payout> = Vec::new();
+    for payout in payouts {
+        current.push(payout);
+        if current.len() > MAX_PAYOUTS_PER_TRANSACTION
+            || transaction_size(&current, sender) > MAX_TRANSACTION_SIZE
+        {
+            current.pop();
+            if current.is_empty() {
+                return Err(format!(
+                    "Row {} does not fit in a transaction, shorten its memo",
+                    payout.line
+                ));
+            }
+            batches.push(std::mem::take(&mut current));
+            current.push(payout);
+        }
+    }
+    if !current.is_empty() {
+        batches.push(current);
+    }
+    Ok(batches)
+}
+
+/// Size of the signed transaction paying `payouts`, compute budget
+/// instructions included.
+fn transaction_size(payouts: &[&Payout], sender: &Pubkey) -> usize {
+    let instructions: Vec<Instruction> = payouts
+        .iter()
+        .flat_map(|payout| payout.instructions(sender))
+        .collect();
+    let message = Message::new(
+        &with_compute_budget(&instructions, MAX_COMPUTE_UNIT_LIMIT, 1),
+        Some(sender),
+    );
+    // One compact-u16 length byte and the signature of the sender.
+    1 + 64 + bincode::serialized_size(&message).unwrap() as usize
+}
+
+fn results_path(csv: &Path) -> PathBuf {
+    let stem = csv.file_stem().unwrap_or_default().to_string_lossy();
+    csv.with_file_name(format!("{}.results.csv", stem))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECIPIENT: &str = "8Wy8nY4QNQoFzRf8kff5FFnJR3LmGmpJpMN2GPrwWMrf";
+
+    fn payout(memo: Option<&str>) -> Payout {
+        Payout {
+            line: 1,
+            recipient: Pubkey::new_unique(),
+            lamports: 1,
+            memo: memo.map(str::to_string),
+            occurrence: 1,
+        }
+    }
+
+    #[test]
+    fn parses_payouts() {
+        let contents = format!(
+            "recipient,amount,memo\n\n{0},0.5\n{0}, 1 ,invoice 7, March\n",
+            RECIPIENT
+        );
+        let payouts = parse_payouts(&contents).unwrap();
+        assert_eq!(payouts.len(), 2);
+        assert_eq!(payouts[0].line, 3);
+        assert_eq!(payouts[0].lamports, 500_000_000);
+        assert_eq!(payouts[0].memo, None);
+        assert_eq!(payouts[1].lamports, 1_000_000_000);
+        assert_eq!(payouts[1].memo.as_deref(), Some("invoice 7, March"));
+    }
+
+    #[test]
+    fn rejects_invalid_payouts() {
+        let contents = format!("{},0.5\nnot-a-key,1", RECIPIENT);
+        let Err(err) = parse_payouts(&contents) else {
+            panic!("line 2 is invalid");
+        };
+        assert!(err.contains("line 2"));
+        for amount in ["0", "-1", "1.0000000001", "abc", ""] {
+            let contents = format!("{},{}", RECIPIENT, amount);
+            assert!(parse_payouts(&contents).is_err(), "{}", amount);
+        }
+    }
+
+    #[test]
+    fn keys_do_not_depend_on_lines() {
+        let row = format!("{},0.5,thanks", RECIPIENT);
+        let first = parse_payouts(&row).unwrap();
+        let moved = parse_payouts(&format!("{},1\n\n{}", RECIPIENT, row)).unwrap();
+        assert_eq!(first[0].key(), moved[1].key());
+        assert_ne!(moved[0].key(), moved[1].key());
+    }
+
+    #[test]
+    fn counts_identical_payouts() {
+        let contents = format!("{0},1\n{0},2\n{0},1", RECIPIENT);
+        let payouts = parse_payouts(&contents).unwrap();
+        let occurrences: Vec<usize> = payouts.iter().map(|payout| payout.occurrence).collect();
+        assert_eq!(occurrences, [1, 1, 2]);
+        assert_ne!(payouts[0].key(), payouts[2].key());
+    }
+
+    #[test]
+    fn parses_results() {
+        let signature = Signature::from([1; 64]);
+        let contents = format!(
+            "unknown,{0},100,{1},1,1,a, b\nsent,{0},,{1},1,1,a, b\nfailed,boom,,{1},2,1,\n",
+            signature, RECIPIENT
+        );
+        let results = parse_results(&contents).unwrap();
+        let outcomes = &results[&format!("{},1,1,a, b", RECIPIENT)];
+        assert!(matches!(
+            outcomes[0],
+            Outcome::Unknown {
+                last_valid_block_height: 100,
+                ..
+            }
+        ));
+        assert!(matches!(outcomes[1], Outcome::Sent));
+        assert!(matches!(
+            results[&format!("{},2,1,", RECIPIENT)][0],
+            Outcome::Failed
+        ));
+        assert!(parse_results("unknown,nope,1,key").is_err());
+        assert!(parse_results("sent,sig").is_err());
+    }
+
+    #[test]
+    fn packs_at_most_the_payouts_per_transaction() {
+        let payouts: Vec<Payout> = (0..25).map(|_| payout(None)).collect();
+        let payouts: Vec<&Payout> = payouts.iter().collect();
+        let batches = pack(&payouts, &Pubkey::new_unique()).unwrap();
+        let sizes: Vec<usize> = batches.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [10, 10, 5]);
+    }
+
+    #[test]
+    fn packs_by_transaction_size() {
+        let memo = "x".repeat(400);
+        let payouts: Vec<Payout> = (0..5).map(|_| payout(Some(&memo))).collect();
+        let payouts: Vec<&Payout> = payouts.iter().collect();
+        let sender = Pubkey::new_unique();
+        let batches = pack(&payouts, &sender).unwrap();
+        assert!(batches.len() > 1);
+        assert_eq!(batches.iter().map(Vec::len).sum::<usize>(), 5);
+        for batch in &batches {
+            assert!(transaction_size(batch, &sender) <= MAX_TRANSACTION_SIZE);
+        }
+    }
+
+    #[test]
+    fn rejects_payouts_too_large_for_a_transaction() {
+        let payout = payout(Some(&"x".repeat(MAX_TRANSACTION_SIZE)));
+        assert!(pack(&[&payout], &Pubkey::new_unique()).is_err());
     }
 }
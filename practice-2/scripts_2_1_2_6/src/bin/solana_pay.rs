@@ -0,0 +1,182 @@
+use scripts_2_1_2_6::cli::{Args, fail, parse_pubkey};
+use scripts_2_1_2_6::load_keypair;
+use scripts_2_1_2_6::pay::{
+    SOL_DECIMALS, TransferRequest, find_reference, format_request_amount, pay_instructions,
+    qr_code, validate_transfer,
+};
+use scripts_2_1_2_6::send::{SendError, TransactionSender};
+use scripts_2_1_2_6::token::{MintInfo, get_mint};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+const USAGE: &str = "Usage: solana_pay <command>
+
+Commands:
+  request <recipient> [--amount=<amount>] [--spl-token=<mint>] [--reference=<address>]
+          [--label=<text>] [--message=<text>] [--memo=<text>]
+  pay <url> [--amount=<amount>]
+  find <url|reference>
+
+request prints a transfer request URL and its QR code.  Unless --reference is
+given a new reference address is generated, `find` uses it to look up the
+payment and, given the URL, checks that the requested amount arrived.
+pay pays a URL, --amount is needed when the URL does not set one.";
+
+fn main() {
+    let args = Args::from_env();
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+
+    match args.required(0, USAGE) {
+        "request" => request(&args),
+        "pay" => pay(&rpc_client, &args),
+        "find" => find(&rpc_client, &args),
+        _ => fail(USAGE),
+    }
+}
+
+fn request(args: &Args) {
+    let mut request = TransferRequest::new(parse_pubkey(args.required(1, USAGE), "recipient"));
+    request.amount = args.option("amount").map(str::to_string);
+    request.spl_token = args
+        .option("spl-token")
+        .map(|mint| parse_pubkey(mint, "spl-token"));
+    request.references = vec![
+        args.option("reference")
+            .map(|reference| parse_pubkey(reference, "reference"))
+            .unwrap_or_else(|| Keypair::new().pubkey()),
+    ];
+    request.label = args.option("label").map(str::to_string);
+    request.message = args.option("message").map(str::to_string);
+    request.memo = args.option("memo").map(str::to_string);
+
+    // Round trip, so a malformed amount is caught here and not by the payer.
+    let url = request.to_url();
+    if let Err(err) = TransferRequest::parse(&url) {
+        fail(&err.to_string());
+    }
+
+    println!("{}", url);
+    match qr_code(&url) {
+        Ok(code) => println!("{}", code),
+        Err(err) => eprintln!("❌ Failed to render QR code: {}", err),
+    }
+    println!("Reference: {}", request.references[0]);
+    println!("Run `solana_pay find '{}'` once paid.", url);
+}
+
+fn pay(rpc_client: &RpcClient, args: &Args) {
+    let url = args.required(1, USAGE);
+    let mut request = TransferRequest::parse(url).unwrap_or_else(|err| fail(&err.to_string()));
+    if request.amount.is_none() {
+        request.amount = args.option("amount").map(str::to_string);
+    }
+
+    let payer = load_keypair();
+    let mint = fetch_mint(rpc_client, &request);
+    let decimals = mint
+        .as_ref()
+        .map_or(SOL_DECIMALS, |mint| mint.mint.decimals);
+    let amount = match request.amount_in(decimals) {
+        Ok(Some(amount)) => amount,
+        Ok(None) => fail("The request has no amount, pass --amount"),
+        Err(err) => fail(&err.to_string()),
+    };
+
+    if let Some(label) = &request.label {
+        println!("Label: {}", label);
+    }
+    if let Some(message) = &request.message {
+        println!("Message: {}", message);
+    }
+    println!(
+        "Paying {} to {}",
+        format_request_amount(amount, mint.as_ref()),
+        request.recipient
+    );
+
+    // Same as `transfer`: the recipient token account is created when it is
+    // missing.
+    let create_recipient_account = mint.as_ref().is_some_and(|mint| {
+        let token_account = get_associated_token_address_with_program_id(
+            &request.recipient,
+            &mint.address,
+            &mint.token_program,
+        );
+        rpc_client.get_account(&token_account).is_err()
+    });
+    let instructions = pay_instructions(
+        &payer.pubkey(),
+        &request,
+        mint.as_ref(),
+        amount,
+        create_recipient_account,
+    );
+
+    match TransactionSender::new(rpc_client, args.flag("dry-run")).send(
+        &instructions,
+        &payer.pubkey(),
+        &[&payer],
+    ) {
+        Ok(signature) => {
+            println!("✅ Paid!");
+            println!("Transaction Signature: {}", signature);
+            println!(
+                "🌐 Explorer Link: https://explorer.solana.com/tx/{}?cluster=devnet",
+                signature
+            );
+        }
+        Err(SendError::DryRun) => {}
+        Err(err) => fail(&format!("Failed to pay: {}", err)),
+    }
+}
+
+fn find(rpc_client: &RpcClient, args: &Args) {
+    let input = args.required(1, USAGE);
+    let request = input
+        .starts_with("solana:")
+        .then(|| TransferRequest::parse(input).unwrap_or_else(|err| fail(&err.to_string())));
+    let reference = match &request {
+        Some(request) => *request
+            .references
+            .first()
+            .unwrap_or_else(|| fail("The request has no reference")),
+        None => parse_pubkey(input, "reference"),
+    };
+
+    let signature = match find_reference(rpc_client, &reference) {
+        Ok(Some(signature)) => signature,
+        Ok(None) => {
+            println!("⏭️  No payment with reference {} yet", reference);
+            return;
+        }
+        Err(err) => fail(&format!("Failed to look up {}: {}", reference, err)),
+    };
+    println!("Transaction Signature: {}", signature);
+
+    let Some(request) = request else {
+        return;
+    };
+    let mint = fetch_mint(rpc_client, &request);
+    let decimals = mint
+        .as_ref()
+        .map_or(SOL_DECIMALS, |mint| mint.mint.decimals);
+    let amount = request
+        .amount_in(decimals)
+        .unwrap_or_else(|err| fail(&err.to_string()))
+        .unwrap_or(0);
+    match validate_transfer(rpc_client, &signature, &request, mint.as_ref(), amount) {
+        Ok(()) => println!(
+            "✅ Payment of {} confirmed",
+            format_request_amount(amount, mint.as_ref())
+        ),
+        Err(err) => fail(&format!("Payment does not match the request: {}", err)),
+    }
+}
+
+fn fetch_mint(rpc_client: &RpcClient, request: &TransferRequest) -> Option<MintInfo> {
+    request.spl_token.map(|mint| {
+        get_mint(rpc_client, &mint)
+            .unwrap_or_else(|err| fail(&format!("Failed to read mint {}: {}", mint, err)))
+    })
+}
@@ -0,0 +1,78 @@
+use scripts_2_1_2_6::cli::{Args, fail, parse_pubkey};
+use scripts_2_1_2_6::load_keypair;
+use scripts_2_1_2_6::memo::memo_instruction;
+use scripts_2_1_2_6::send::{SendError, TransactionSender};
+use scripts_2_1_2_6::token::{format_amount, get_mint, parse_ui_amount, transfer_instructions};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::signature::Signer;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+const USAGE: &str = "Usage: transfer <mint> <recipient_wallet> <amount> [--memo=<text>]";
+
+fn main() {
+    let args = Args::from_env();
+    let mint = parse_pubkey(args.required(0, USAGE), "mint");
+    let recipient = parse_pubkey(args.required(1, USAGE), "recipient");
+    let amount = args.required(2, USAGE);
+
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+    let sender = load_keypair();
+
+    // Works for both Token and Token-2022 mints, the owner tells us which one.
+    let mint_info = get_mint(&rpc_client, &mint)
+        .unwrap_or_else(|err| fail(&format!("Failed to read mint {}: {}", mint, err)));
+    let decimals = mint_info.mint.decimals;
+    let token_program = mint_info.token_program;
+
+    let amount = parse_ui_amount(amount, decimals).unwrap_or_else(|| {
+        fail(&format!(
+            "Invalid amount {} for a mint with {} decimals",
+            amount, decimals
+        ))
+    });
+
+    let source_token_account =
+        get_associated_token_address_with_program_id(&sender.pubkey(), &mint, &token_program);
+    let recipient_token_account =
+        get_associated_token_address_with_program_id(&recipient, &mint, &token_program);
+
+    println!("From token account: {}", source_token_account);
+    println!("To token account: {}", recipient_token_account);
+
+    let create_recipient_account = rpc_client.get_account(&recipient_token_account).is_err();
+    if create_recipient_account {
+        println!("Recipient token account does not exist, it will be created.");
+    }
+
+    let mut instructions = transfer_instructions(
+        &sender.pubkey(),
+        &recipient,
+        &mint_info,
+        amount,
+        create_recipient_account,
+    );
+
+    if let Some(memo) = args.option("memo") {
+        instructions.push(memo_instruction(memo, &[&sender.pubkey()]));
+    }
+
+    // Simulate, sign, send and confirm the transaction
+    match TransactionSender::new(&rpc_client, args.flag("dry-run")).send(
+        &instructions,
+        &sender.pubkey(),
+        &[&sender],
+    ) {
+        Ok(signature) => {
+            println!(
+                "✅ Transferred {} tokens to {}",
+                format_amount(amount, decimals),
+                recipient
+            );
+            println!("Transaction Signature: {}", signature);
+        }
+        Err(SendError::DryRun) => {}
+        Err(err) => {
+            eprintln!("❌ Failed to transfer tokens: {}", err);
+        }
+    };
+}
@@ -0,0 +1,146 @@
+use scripts_2_1_2_6::cli::{Args, fail};
+use scripts_2_1_2_6::load_keypair;
+use scripts_2_1_2_6::send::{SendError, TransactionSender};
+use scripts_2_1_2_6::token::parse_ui_amount;
+use scripts_2_1_2_6::wsol::{
+    WsolBalance, get_wsol_balance, unwrap_instruction, wrap_instructions, wsol_address,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction, native_token::lamports_to_sol, pubkey::Pubkey, signature::Signer,
+};
+
+const USAGE: &str = "Usage: wsol <command>
+
+Commands:
+  balance
+  wrap <amount_sol> [--top-up]
+  unwrap
+  sync
+
+wrap moves SOL into our wrapped SOL account, with --top-up only as much as
+is missing to hold <amount_sol>.  unwrap closes the account and returns
+everything as SOL.  sync wraps lamports that were sent to the account
+directly.";
+
+fn main() {
+    let args = Args::from_env();
+    let rpc_client = RpcClient::new("https://api.devnet.solana.com");
+    let owner = load_keypair();
+
+    match args.required(0, USAGE) {
+        "balance" => {}
+        "wrap" => wrap(&rpc_client, &args, &owner),
+        "unwrap" => unwrap(&rpc_client, &args, &owner),
+        "sync" => sync(&rpc_client, &args, &owner),
+        _ => fail(USAGE),
+    }
+    print_balance(&rpc_client, &owner.pubkey());
+}
+
+fn wrap(rpc_client: &RpcClient, args: &Args, owner: &impl Signer) {
+    let amount = args.required(1, USAGE);
+    let mut lamports =
+        parse_ui_amount(amount, 9).unwrap_or_else(|| fail(&format!("Invalid amount: {}", amount)));
+
+    if args.flag("top-up") {
+        let wrapped = fetch_balance(rpc_client, &owner.pubkey())
+            .map(|balance| balance.amount + balance.unsynced)
+            .unwrap_or(0);
+        lamports = lamports.saturating_sub(wrapped);
+        if lamports == 0 {
+            println!("⏭️  Already holding {} wrapped SOL", amount);
+            return;
+        }
+    }
+
+    let success = format!("Wrapped {} SOL", lamports_to_sol(lamports));
+    send(
+        rpc_client,
+        args,
+        &wrap_instructions(&owner.pubkey(), lamports),
+        owner,
+        &success,
+    );
+}
+
+fn unwrap(rpc_client: &RpcClient, args: &Args, owner: &impl Signer) {
+    let Some(balance) = fetch_balance(rpc_client, &owner.pubkey()) else {
+        println!("⏭️  No wrapped SOL account, nothing to unwrap");
+        return;
+    };
+
+    let success = format!(
+        "Unwrapped {} SOL, rent included",
+        lamports_to_sol(balance.lamports)
+    );
+    send(
+        rpc_client,
+        args,
+        &[unwrap_instruction(&owner.pubkey())],
+        owner,
+        &success,
+    );
+}
+
+fn sync(rpc_client: &RpcClient, args: &Args, owner: &impl Signer) {
+    let Some(balance) = fetch_balance(rpc_client, &owner.pubkey()) else {
+        println!("⏭️  No wrapped SOL account, nothing to sync");
+        return;
+    };
+    if balance.unsynced == 0 {
+        println!("⏭️  Wrapped SOL account is already in sync");
+        return;
+    }
+
+    let success = format!("Synced {} SOL", lamports_to_sol(balance.unsynced));
+    send(
+        rpc_client,
+        args,
+        &wrap_instructions(&owner.pubkey(), 0),
+        owner,
+        &success,
+    );
+}
+
+fn print_balance(rpc_client: &RpcClient, owner: &Pubkey) {
+    println!("Wrapped SOL account: {}", wsol_address(owner));
+    match fetch_balance(rpc_client, owner) {
+        None => println!("Wrapped SOL balance: 0 SOL (no account)"),
+        Some(balance) => {
+            println!(
+                "Wrapped SOL balance: {} SOL",
+                lamports_to_sol(balance.amount)
+            );
+            if balance.unsynced > 0 {
+                println!(
+                    "Not yet synced: {} SOL, run `wsol sync`",
+                    lamports_to_sol(balance.unsynced)
+                );
+            }
+        }
+    }
+}
+
+fn fetch_balance(rpc_client: &RpcClient, owner: &Pubkey) -> Option<WsolBalance> {
+    get_wsol_balance(rpc_client, owner)
+        .unwrap_or_else(|err| fail(&format!("Failed to read wrapped SOL account: {}", err)))
+}
+
+fn send(
+    rpc_client: &RpcClient,
+    args: &Args,
+    instructions: &[Instruction],
+    owner: &impl Signer,
+    success: &str,
+) {
+    let sender = TransactionSender::new(rpc_client, args.flag("dry-run"));
+    match sender.send(instructions, &owner.pubkey(), &[owner]) {
+        Ok(signature) => {
+            println!("✅ {}!", success);
+            println!("Transaction Signature: {}", signature);
+        }
+        Err(SendError::DryRun) => {}
+        Err(err) => fail(&format!("Failed: {}", err)),
+    }
+}
@@ -0,0 +1,77 @@
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::process::exit;
+use std::str::FromStr;
+
+/// Command line arguments of a script.
+///
+/// Positional arguments are kept in order, `--name=value` is stored as an
+/// option and a bare `--name` is stored as a flag.
+pub struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, Option<String>>,
+}
+
+impl Args {
+    pub fn from_env() -> Self {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+
+        for arg in args {
+            match arg.strip_prefix("--") {
+                Some(option) => match option.split_once('=') {
+                    Some((name, value)) => {
+                        options.insert(name.to_string(), Some(value.to_string()));
+                    }
+                    None => {
+                        options.insert(option.to_string(), None);
+                    }
+                },
+                None => positional.push(arg),
+            }
+        }
+
+        Args {
+            positional,
+            options,
+        }
+    }
+
+    pub fn positional(&self, index: usize) -> Option<&str> {
+        self.positional.get(index).map(String::as_str)
+    }
+
+    /// Returns a required positional argument, or exits with `usage`.
+    pub fn required(&self, index: usize, usage: &str) -> &str {
+        self.positional(index).unwrap_or_else(|| fail(usage))
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).and_then(|value| value.as_deref())
+    }
+}
+
+/// Parses a value given on the command line, or exits naming the argument.
+pub fn parse<T: FromStr>(value: &str, name: &str) -> T {
+    value
+        .parse()
+        .unwrap_or_else(|_| fail(&format!("Invalid {}: {}", name, value)))
+}
+
+pub fn parse_pubkey(value: &str, name: &str) -> Pubkey {
+    parse(value, name)
+}
+
+/// Prints an error and exits with a non-zero status.
+pub fn fail(message: &str) -> ! {
+    eprintln!("❌ {}", message);
+    exit(1);
+}
@@ -0,0 +1,200 @@
+use crate::inspect::ESCROW_PROGRAM_IDS;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use std::error::Error;
+
+/// First 8 bytes of sha256("account:Offer"), Anchor's discriminator of offer
+/// accounts.
+const OFFER_DISCRIMINATOR: [u8; 8] = [215, 88, 60, 71, 170, 162, 73, 229];
+
+/// Offset of `allowed_taker` in an offer account: the discriminator, the id,
+/// three keys and four amounts come first.
+const ALLOWED_TAKER_OFFSET: usize = 8 + 8 + 32 * 3 + 8 * 4;
+
+/// An offer of the escrow program of practice 4.
+pub struct Offer {
+    pub address: Pubkey,
+    pub id: u64,
+    pub maker: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+    pub token_a_remaining_amount: u64,
+    pub token_b_remaining_amount: u64,
+    pub allowed_taker: Option<Pubkey>,
+    pub expires_at: Option<i64>,
+}
+
+impl Offer {
+    pub fn unpack(address: Pubkey, data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut data = data
+            .strip_prefix(&OFFER_DISCRIMINATOR)
+            .ok_or_else(|| format!("{} is not an offer", address))?;
+        Self::read(address, &mut data)
+            .ok_or_else(|| format!("Offer {} is truncated", address).into())
+    }
+
+    fn read(address: Pubkey, data: &mut &[u8]) -> Option<Self> {
+        Some(Offer {
+            address,
+            id: u64::from_le_bytes(take(data)?),
+            maker: Pubkey::new_from_array(take(data)?),
+            token_mint_a: Pubkey::new_from_array(take(data)?),
+            token_mint_b: Pubkey::new_from_array(take(data)?),
+            token_a_offered_amount: u64::from_le_bytes(take(data)?),
+            token_b_wanted_amount: u64::from_le_bytes(take(data)?),
+            token_a_remaining_amount: u64::from_le_bytes(take(data)?),
+            token_b_remaining_amount: u64::from_le_bytes(take(data)?),
+            allowed_taker: take_option(data, |data| Some(Pubkey::new_from_array(take(data)?)))?,
+            expires_at: take_option(data, |data| Some(i64::from_le_bytes(take(data)?)))?,
+        })
+    }
+
+    /// Whether the maker wants lamports, the offer then has no mint B.
+    pub fn wants_sol(&self) -> bool {
+        self.token_mint_b == Pubkey::default()
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+fn take<const N: usize>(data: &mut &[u8]) -> Option<[u8; N]> {
+    let (bytes, rest) = data.split_first_chunk::<N>()?;
+    *data = rest;
+    Some(*bytes)
+}
+
+/// Reads a Borsh `Option<T>`.
+fn take_option<T>(
+    data: &mut &[u8],
+    read: impl FnOnce(&mut &[u8]) -> Option<T>,
+) -> Option<Option<T>> {
+    match take::<1>(data)? {
+        [0] => Some(None),
+        _ => read(data).map(Some),
+    }
+}
+
+/// An offer account as read by `get_offers`: the offer, or why it isn't one.
+pub type Unpacked = Result<Offer, Box<dyn Error>>;
+
+/// Open offers that only `taker` may take.  Every account is unpacked on its
+/// own, so one that can't be read doesn't hide the others.
+pub fn get_offers_for_taker(
+    rpc_client: &RpcClient,
+    taker: &Pubkey,
+) -> Result<Vec<Unpacked>, Box<dyn Error>> {
+    // `Some` followed by the key.
+    let mut allowed_taker = vec![1];
+    allowed_taker.extend_from_slice(taker.as_ref());
+    get_offers(
+        rpc_client,
+        vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            ALLOWED_TAKER_OFFSET,
+            &allowed_taker,
+        ))],
+    )
+}
+
+fn get_offers(
+    rpc_client: &RpcClient,
+    mut filters: Vec<RpcFilterType>,
+) -> Result<Vec<Unpacked>, Box<dyn Error>> {
+    filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+        0,
+        &OFFER_DISCRIMINATOR,
+    )));
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    Ok(rpc_client
+        .get_program_accounts_with_config(&ESCROW_PROGRAM_IDS[0], config)?
+        .into_iter()
+        .map(|(address, account)| Offer::unpack(address, &account.data))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An offer account as the program stores it, `bump` included.
+    fn offer_data(allowed_taker: Option<Pubkey>, expires_at: Option<i64>) -> Vec<u8> {
+        let mut data = OFFER_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&7u64.to_le_bytes());
+        for key in [[1; 32], [2; 32], [3; 32]] {
+            data.extend_from_slice(&key);
+        }
+        for amount in [100u64, 200, 40, 80] {
+            data.extend_from_slice(&amount.to_le_bytes());
+        }
+        match allowed_taker {
+            Some(taker) => {
+                data.push(1);
+                data.extend_from_slice(taker.as_ref());
+            }
+            None => data.push(0),
+        }
+        match expires_at {
+            Some(expires_at) => {
+                data.push(1);
+                data.extend_from_slice(&expires_at.to_le_bytes());
+            }
+            None => data.push(0),
+        }
+        data.push(255);
+        data
+    }
+
+    #[test]
+    fn unpacks_offers() {
+        let taker = Pubkey::new_unique();
+        let offer = Offer::unpack(
+            Pubkey::default(),
+            &offer_data(Some(taker), Some(1_700_000_000)),
+        )
+        .unwrap();
+        assert_eq!(offer.id, 7);
+        assert_eq!(offer.maker, Pubkey::new_from_array([1; 32]));
+        assert_eq!(offer.token_mint_a, Pubkey::new_from_array([2; 32]));
+        assert_eq!(offer.token_mint_b, Pubkey::new_from_array([3; 32]));
+        assert_eq!(offer.token_a_offered_amount, 100);
+        assert_eq!(offer.token_b_wanted_amount, 200);
+        assert_eq!(offer.token_a_remaining_amount, 40);
+        assert_eq!(offer.token_b_remaining_amount, 80);
+        assert_eq!(offer.allowed_taker, Some(taker));
+        assert_eq!(offer.expires_at, Some(1_700_000_000));
+
+        let offer = Offer::unpack(Pubkey::default(), &offer_data(None, Some(5))).unwrap();
+        assert_eq!(offer.allowed_taker, None);
+        assert_eq!(offer.expires_at, Some(5));
+    }
+
+    #[test]
+    fn allowed_taker_is_at_its_offset() {
+        let taker = Pubkey::new_unique();
+        let data = offer_data(Some(taker), Some(5));
+        // The filter of `get_offers_for_taker`: `Some` followed by the key.
+        assert_eq!(data[ALLOWED_TAKER_OFFSET], 1);
+        assert_eq!(&data[ALLOWED_TAKER_OFFSET + 1..][..32], taker.as_ref());
+    }
+
+    #[test]
+    fn rejects_other_and_truncated_accounts() {
+        let data = offer_data(Some(Pubkey::new_unique()), None);
+        assert!(Offer::unpack(Pubkey::default(), &data[..ALLOWED_TAKER_OFFSET + 8]).is_err());
+        assert!(Offer::unpack(Pubkey::default(), &data[8..]).is_err());
+    }
+}
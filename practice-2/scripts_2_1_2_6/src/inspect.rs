@@ -0,0 +1,620 @@
+use solana_sdk::{
+    compute_budget, hash::hashv, pubkey, pubkey::Pubkey, system_instruction::SystemInstruction,
+    system_program,
+};
+use spl_token_2022::instruction::TokenInstruction;
+
+/// The escrow program of practice 4, and the older one of practice 3.
+pub const ESCROW_PROGRAM_IDS: [Pubkey; 2] = [
+    pubkey!("5MaJShYoCFPtoghs5pTyVHa44w4BBvxCTJsZCjnCe3y1"),
+    pubkey!("FXAxfUgPgK5WZMcKkCqBC28twcvg57LowieqtSnJD2BE"),
+];
+
+pub const FAVORITES_PROGRAM_ID: Pubkey = pubkey!("G8bVjrSqkqGa5hhjsLdn1ycMaYbU1mqqZhTc1DCMhgpA");
+
+/// An instruction in readable form.
+pub struct DecodedInstruction {
+    pub program: &'static str,
+    pub name: String,
+    pub args: Vec<(&'static str, String)>,
+    /// Names of the accounts, in order, when the program's layout is known.
+    pub account_names: &'static [&'static str],
+}
+
+impl DecodedInstruction {
+    fn new(program: &'static str, name: impl Into<String>) -> Self {
+        DecodedInstruction {
+            program,
+            name: name.into(),
+            args: Vec::new(),
+            account_names: &[],
+        }
+    }
+}
+
+/// Decodes an instruction of one of the programs we work with.  Instructions
+/// that cannot be decoded are shown with their raw data.
+pub fn decode_instruction(program_id: &Pubkey, data: &[u8]) -> DecodedInstruction {
+    let decoded = if *program_id == system_program::id() {
+        decode_system(data)
+    } else if *program_id == spl_token::id() {
+        decode_token("Token Program", data)
+    } else if *program_id == spl_token_2022::id() {
+        decode_token("Token-2022 Program", data)
+    } else if *program_id == spl_associated_token_account::id() {
+        decode_associated_token(data)
+    } else if crate::memo::is_memo_program(program_id) {
+        Some(DecodedInstruction {
+            args: vec![("text", String::from_utf8_lossy(data).into_owned())],
+            ..DecodedInstruction::new("Memo Program", "memo")
+        })
+    } else if *program_id == compute_budget::id() {
+        decode_compute_budget(data)
+    } else if *program_id == mpl_token_metadata::ID {
+        decode_metadata(data)
+    } else if *program_id == ESCROW_PROGRAM_IDS[0] {
+        decode_escrow(
+            data,
+            &[
+                "make_offer",
+                "take_offer",
+                "close_offer",
+                "refund_expired_offer",
+                "update_offer",
+                "initialize_config",
+                "update_config",
+            ],
+            &[escrow_layout, original_escrow_layout],
+        )
+    } else if *program_id == ESCROW_PROGRAM_IDS[1] {
+        decode_escrow(
+            data,
+            &["make_offer", "take_offer"],
+            &[delegate_escrow_layout],
+        )
+    } else if *program_id == FAVORITES_PROGRAM_ID {
+        decode_favorites(data)
+    } else {
+        None
+    };
+
+    decoded.unwrap_or_else(|| DecodedInstruction {
+        args: vec![("data", hex(data))],
+        ..DecodedInstruction::new("Unknown program", "unknown")
+    })
+}
+
+fn decode_system(data: &[u8]) -> Option<DecodedInstruction> {
+    let instruction: SystemInstruction = bincode::deserialize(data).ok()?;
+    Some(DecodedInstruction::new(
+        "System Program",
+        format!("{:?}", instruction),
+    ))
+}
+
+fn decode_token(program: &'static str, data: &[u8]) -> Option<DecodedInstruction> {
+    // Token-2022 understands every instruction of the original program.
+    let instruction = TokenInstruction::unpack(data).ok()?;
+    Some(DecodedInstruction::new(
+        program,
+        format!("{:?}", instruction),
+    ))
+}
+
+fn decode_associated_token(data: &[u8]) -> Option<DecodedInstruction> {
+    let name = match data.first() {
+        None | Some(0) => "Create",
+        Some(1) => "CreateIdempotent",
+        Some(2) => "RecoverNested",
+        _ => return None,
+    };
+    Some(DecodedInstruction {
+        account_names: &[
+            "payer",
+            "associated_token_account",
+            "wallet",
+            "mint",
+            "system_program",
+            "token_program",
+        ],
+        ..DecodedInstruction::new("Associated Token Program", name)
+    })
+}
+
+fn decode_compute_budget(data: &[u8]) -> Option<DecodedInstruction> {
+    let mut reader = Reader::new(data);
+    let (name, arg, value) = match reader.u8()? {
+        1 => ("RequestHeapFrame", "bytes", reader.u32()?.to_string()),
+        2 => ("SetComputeUnitLimit", "units", reader.u32()?.to_string()),
+        3 => (
+            "SetComputeUnitPrice",
+            "micro_lamports",
+            reader.u64()?.to_string(),
+        ),
+        4 => (
+            "SetLoadedAccountsDataSizeLimit",
+            "bytes",
+            reader.u32()?.to_string(),
+        ),
+        _ => return None,
+    };
+    Some(DecodedInstruction {
+        args: vec![(arg, value)],
+        ..DecodedInstruction::new("Compute Budget Program", name)
+    })
+}
+
+fn decode_metadata(data: &[u8]) -> Option<DecodedInstruction> {
+    let mut reader = Reader::new(data);
+    let program = "Metaplex Token Metadata";
+    let decoded = match reader.u8()? {
+        17 => DecodedInstruction {
+            args: vec![("max_supply", reader.option(Reader::u64)?)],
+            ..DecodedInstruction::new(program, "CreateMasterEditionV3")
+        },
+        33 => DecodedInstruction {
+            args: vec![
+                ("name", reader.string()?),
+                ("symbol", reader.string()?),
+                ("uri", reader.string()?),
+                ("seller_fee_basis_points", reader.u16()?.to_string()),
+            ],
+            ..DecodedInstruction::new(program, "CreateMetadataAccountV3")
+        },
+        discriminator => {
+            let name = match discriminator {
+                15 => "UpdateMetadataAccountV2",
+                18 => "VerifyCollection",
+                22 => "UnverifyCollection",
+                25 => "SetAndVerifyCollection",
+                29 => "BurnNft",
+                30 => "VerifySizedCollectionItem",
+                31 => "UnverifySizedCollectionItem",
+                32 => "SetAndVerifySizedCollectionItem",
+                41 => "Burn",
+                42 => "Create",
+                43 => "Mint",
+                44 => "Delegate",
+                45 => "Revoke",
+                49 => "Transfer",
+                50 => "Update",
+                52 => "Verify",
+                53 => "Unverify",
+                _ => return None,
+            };
+            DecodedInstruction::new(program, name)
+        }
+    };
+    Some(decoded)
+}
+
+/// Decodes an escrow instruction with the first of `layouts` that reads its
+/// arguments exactly.  When none does, e.g. for a version of the program we
+/// don't know, the name from the discriminator is still shown.
+fn decode_escrow(
+    data: &[u8],
+    names: &[&str],
+    layouts: &[EscrowLayout],
+) -> Option<DecodedInstruction> {
+    let (name, reader) = anchor_instruction(data, names)?;
+    let decoded = layouts
+        .iter()
+        .find_map(|layout| {
+            let mut reader = reader.clone();
+            let decoded = layout(name, &mut reader)?;
+            reader.is_empty().then_some(decoded)
+        })
+        .unwrap_or_else(|| DecodedInstruction {
+            args: vec![("data", hex(reader.data))],
+            ..DecodedInstruction::new("Escrow", name)
+        });
+    Some(decoded)
+}
+
+/// Reads the arguments and names the accounts of one escrow instruction, as
+/// one version of the program laid them out.
+type EscrowLayout = fn(&str, &mut Reader) -> Option<DecodedInstruction>;
+
+/// The escrow of practice 4, with expiry, partial fills, fees and slippage
+/// limits.
+fn escrow_layout(name: &str, reader: &mut Reader) -> Option<DecodedInstruction> {
+    let decoded = match name {
+        "make_offer" => DecodedInstruction {
+            args: vec![
+                ("id", reader.u64()?.to_string()),
+                ("token_a_offered_amount", reader.u64()?.to_string()),
+                ("token_b_wanted_amount", reader.u64()?.to_string()),
+                ("expires_at", reader.option(Reader::i64)?),
+                ("allowed_taker", reader.option(Reader::pubkey)?),
+            ],
+            account_names: &[
+                "maker",
+                "token_mint_a",
+                "token_mint_b",
+                "maker_token_account_a",
+                "offer",
+                "vault",
+                "associated_token_program",
+                "token_program",
+                "token_program_b",
+                "system_program",
+            ],
+            ..DecodedInstruction::new("Escrow", name)
+        },
+        "take_offer" => DecodedInstruction {
+            args: vec![
+                ("token_a_amount", reader.u64()?.to_string()),
+                ("max_token_b_to_pay", reader.u64()?.to_string()),
+                ("min_token_a_to_receive", reader.u64()?.to_string()),
+            ],
+            account_names: &[
+                "taker",
+                "maker",
+                "token_mint_a",
+                "token_mint_b",
+                "taker_token_account_a",
+                "taker_token_account_b",
+                "maker_token_account_b",
+                "offer",
+                "vault",
+                "config",
+                "treasury",
+                "treasury_token_account_b",
+                "associated_token_program",
+                "token_program",
+                "token_program_b",
+                "system_program",
+            ],
+            ..DecodedInstruction::new("Escrow", name)
+        },
+        "refund_expired_offer" => DecodedInstruction {
+            account_names: &[
+                "keeper",
+                "maker",
+                "offer",
+                "vault",
+                "token_mint_a",
+                "maker_token_account_a",
+                "associated_token_program",
+                "token_program",
+                "system_program",
+            ],
+            ..DecodedInstruction::new("Escrow", name)
+        },
+        "update_offer" => DecodedInstruction {
+            args: vec![
+                ("token_a_amount", reader.u64()?.to_string()),
+                ("token_b_wanted_amount", reader.u64()?.to_string()),
+            ],
+            account_names: &[
+                "maker",
+                "offer",
+                "vault",
+                "token_mint_a",
+                "maker_token_account_a",
+                "associated_token_program",
+                "token_program",
+                "system_program",
+            ],
+            ..DecodedInstruction::new("Escrow", name)
+        },
+        "initialize_config" => DecodedInstruction {
+            args: vec![
+                ("fee_bps", reader.u16()?.to_string()),
+                ("treasury", reader.pubkey()?.to_string()),
+            ],
+            account_names: &[
+                "admin",
+                "config",
+                "program",
+                "program_data",
+                "system_program",
+            ],
+            ..DecodedInstruction::new("Escrow", name)
+        },
+        "update_config" => DecodedInstruction {
+            args: vec![
+                ("admin", reader.pubkey()?.to_string()),
+                ("fee_bps", reader.u16()?.to_string()),
+                ("treasury", reader.pubkey()?.to_string()),
+            ],
+            account_names: &["admin", "config"],
+            ..DecodedInstruction::new("Escrow", name)
+        },
+        "close_offer" => DecodedInstruction {
+            account_names: &[
+                "maker",
+                "offer",
+                "vault",
+                "token_mint_a",
+                "maker_token_account_a",
+                "associated_token_program",
+                "token_program",
+                "system_program",
+            ],
+            ..DecodedInstruction::new("Escrow", name)
+        },
+        _ => return None,
+    };
+    Some(decoded)
+}
+
+/// The first escrow of practice 4, offers were taken whole and never expired.
+fn original_escrow_layout(name: &str, reader: &mut Reader) -> Option<DecodedInstruction> {
+    let decoded = match name {
+        "make_offer" => DecodedInstruction {
+            args: vec![
+                ("id", reader.u64()?.to_string()),
+                ("token_a_offered_amount", reader.u64()?.to_string()),
+                ("token_b_wanted_amount", reader.u64()?.to_string()),
+            ],
+            account_names: &[
+                "maker",
+                "token_mint_a",
+                "token_mint_b",
+                "maker_token_account_a",
+                "offer",
+                "vault",
+                "associated_token_program",
+                "token_program",
+                "system_program",
+            ],
+            ..DecodedInstruction::new("Escrow", name)
+        },
+        "take_offer" => DecodedInstruction {
+            account_names: &[
+                "taker",
+                "maker",
+                "token_mint_a",
+                "token_mint_b",
+                "taker_token_account_a",
+                "taker_token_account_b",
+                "maker_token_account_b",
+                "offer",
+                "vault",
+                "associated_token_program",
+                "token_program",
+                "system_program",
+            ],
+            ..DecodedInstruction::new("Escrow", name)
+        },
+        "close_offer" => DecodedInstruction {
+            account_names: &[
+                "maker",
+                "offer",
+                "vault",
+                "token_mint_a",
+                "maker_token_account_a",
+                "associated_token_program",
+                "token_program",
+                "system_program",
+            ],
+            ..DecodedInstruction::new("Escrow", name)
+        },
+        _ => return None,
+    };
+    Some(decoded)
+}
+
+/// The escrow of practice 3, tokens stay with the maker and a delegate moves
+/// them.
+fn delegate_escrow_layout(name: &str, reader: &mut Reader) -> Option<DecodedInstruction> {
+    let decoded = match name {
+        "make_offer" => DecodedInstruction {
+            args: vec![
+                ("id", reader.u64()?.to_string()),
+                ("token_a_offered_amount", reader.u64()?.to_string()),
+                ("token_b_wanted_amount", reader.u64()?.to_string()),
+            ],
+            account_names: &[
+                "maker",
+                "token_mint_a",
+                "token_mint_b",
+                "maker_token_account_a",
+                "offer",
+                "delegate",
+                "associated_token_program",
+                "token_program",
+                "system_program",
+            ],
+            ..DecodedInstruction::new("Escrow", name)
+        },
+        "take_offer" => DecodedInstruction {
+            account_names: &[
+                "taker",
+                "maker",
+                "token_mint_a",
+                "token_mint_b",
+                "taker_token_account_a",
+                "taker_token_account_b",
+                "maker_token_account_b",
+                "maker_token_account_a",
+                "offer",
+                "delegate",
+                "associated_token_program",
+                "token_program",
+                "system_program",
+            ],
+            ..DecodedInstruction::new("Escrow", name)
+        },
+        _ => return None,
+    };
+    Some(decoded)
+}
+
+fn decode_favorites(data: &[u8]) -> Option<DecodedInstruction> {
+    let (name, mut reader) = anchor_instruction(
+        data,
+        &[
+            "initialize",
+            "set_favorites",
+            "update_favorites",
+            "set_authority",
+            "update_favorites_by_authority",
+        ],
+    )?;
+    let program = "Favorites";
+    let decoded = match name {
+        "initialize" => DecodedInstruction::new(program, name),
+        "set_favorites" => DecodedInstruction {
+            args: vec![
+                ("number", reader.u64()?.to_string()),
+                ("color", reader.string()?),
+            ],
+            account_names: &["user", "favorites", "system_program"],
+            ..DecodedInstruction::new(program, name)
+        },
+        "set_authority" => DecodedInstruction {
+            args: vec![("authority", reader.option(Reader::pubkey)?)],
+            account_names: &["id", "user", "authority", "favorites", "system_program"],
+            ..DecodedInstruction::new(program, name)
+        },
+        "update_favorites" => DecodedInstruction {
+            args: vec![
+                ("number", reader.option(Reader::u64)?),
+                ("color", reader.option(Reader::string)?),
+            ],
+            account_names: &["user", "favorites", "system_program"],
+            ..DecodedInstruction::new(program, name)
+        },
+        _ => DecodedInstruction {
+            args: vec![
+                ("number", reader.option(Reader::u64)?),
+                ("color", reader.option(Reader::string)?),
+            ],
+            account_names: &["authority", "authority_data", "favorites", "system_program"],
+            ..DecodedInstruction::new(program, name)
+        },
+    };
+    Some(decoded)
+}
+
+/// Finds which of `names` an Anchor instruction calls, using the 8 byte
+/// discriminator Anchor derives from `global:<name>`.
+fn anchor_instruction<'a, 'd>(data: &'d [u8], names: &[&'a str]) -> Option<(&'a str, Reader<'d>)> {
+    let (discriminator, args) = data.split_at_checked(8)?;
+    let name = names
+        .iter()
+        .find(|name| hashv(&[b"global:", name.as_bytes()]).to_bytes()[..8] == *discriminator)?;
+    Some((name, Reader::new(args)))
+}
+
+/// Reads Borsh encoded instruction arguments.
+#[derive(Clone)]
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (bytes, rest) = self.data.split_first_chunk::<N>()?;
+        self.data = rest;
+        Some(*bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take::<1>().map(|[byte]| byte)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    fn i64(&mut self) -> Option<i64> {
+        self.take().map(i64::from_le_bytes)
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        self.take().map(Pubkey::new_from_array)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        let (bytes, rest) = self.data.split_at_checked(len)?;
+        self.data = rest;
+        Some(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    /// Reads an `Option<T>` and formats it, `None` is shown as `none`.
+    fn option<T: ToString>(&mut self, read: fn(&mut Self) -> Option<T>) -> Option<String> {
+        match self.u8()? {
+            0 => Some("none".to_string()),
+            _ => read(self).map(|value| value.to_string()),
+        }
+    }
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchor_data(name: &str, args: &[u64]) -> Vec<u8> {
+        let mut data = hashv(&[b"global:", name.as_bytes()]).to_bytes()[..8].to_vec();
+        for arg in args {
+            data.extend_from_slice(&arg.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn decodes_current_escrow_layout() {
+        let mut data = anchor_data("make_offer", &[1, 100, 200]);
+        data.extend_from_slice(&[0, 0]);
+        let decoded = decode_instruction(&ESCROW_PROGRAM_IDS[0], &data);
+        assert_eq!(decoded.name, "make_offer");
+        assert_eq!(decoded.args.len(), 5);
+        assert!(decoded.account_names.contains(&"token_program_b"));
+    }
+
+    #[test]
+    fn decodes_original_escrow_layout() {
+        let data = anchor_data("make_offer", &[1, 100, 200]);
+        let decoded = decode_instruction(&ESCROW_PROGRAM_IDS[0], &data);
+        assert_eq!(decoded.name, "make_offer");
+        assert_eq!(decoded.args.len(), 3);
+        assert!(!decoded.account_names.contains(&"token_program_b"));
+
+        let decoded = decode_instruction(&ESCROW_PROGRAM_IDS[0], &anchor_data("take_offer", &[]));
+        assert_eq!(decoded.name, "take_offer");
+        assert!(decoded.args.is_empty());
+        assert!(!decoded.account_names.contains(&"config"));
+    }
+
+    #[test]
+    fn decodes_practice_3_escrow() {
+        let data = anchor_data("take_offer", &[]);
+        let decoded = decode_instruction(&ESCROW_PROGRAM_IDS[1], &data);
+        assert_eq!(decoded.program, "Escrow");
+        assert_eq!(decoded.name, "take_offer");
+        assert!(decoded.account_names.contains(&"delegate"));
+    }
+
+    #[test]
+    fn names_escrow_instructions_with_unknown_args() {
+        let data = anchor_data("take_offer", &[5]);
+        let decoded = decode_instruction(&ESCROW_PROGRAM_IDS[0], &data);
+        assert_eq!(decoded.name, "take_offer");
+        assert_eq!(decoded.args, vec![("data", "0500000000000000".to_string())]);
+        assert!(decoded.account_names.is_empty());
+    }
+}
@@ -1,16 +1,67 @@
-use solana_sdk::signature::read_keypair_file;
+pub mod cli;
+pub mod escrow;
+pub mod inspect;
+pub mod lookup_table;
+pub mod memo;
+pub mod nft;
+pub mod nonce;
+pub mod pay;
+pub mod send;
+pub mod snapshot;
+pub mod token;
+pub mod wsol;
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::sanitize::Sanitize;
+use solana_sdk::signature::Signature;
 use solana_sdk::signature::Signer;
+use solana_sdk::signature::read_keypair_file;
+use solana_sdk::transaction::Transaction;
+use solana_transaction_status_client_types::{
+    EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding,
+};
+use std::error::Error;
 use std::path::Path;
 
 pub fn load_keypair() -> impl Signer {
     let keypair_path = "./config/pk.json";
 
-    let keypair = read_keypair_file(&Path::new(keypair_path)).expect("Failed to read keypair file");
-
-    return keypair;
+    read_keypair_file(Path::new(keypair_path)).expect("Failed to read keypair file")
 }
 
 pub fn get_pubkey() -> String {
     let keypair = load_keypair();
-    return keypair.pubkey().to_string();
+    keypair.pubkey().to_string()
+}
+
+/// Serializes a (possibly partially signed) transaction as base64, the same
+/// encoding `solana` CLI and web3.js use for offline signing.
+pub fn encode_transaction(transaction: &Transaction) -> String {
+    BASE64.encode(bincode::serialize(transaction).expect("Transaction is always serializable"))
+}
+
+pub fn decode_transaction(encoded: &str) -> Result<Transaction, Box<dyn Error>> {
+    let bytes = BASE64.decode(encoded.trim())?;
+    let transaction: Transaction = bincode::deserialize(&bytes)?;
+    // Rejects signatures and account indices that don't match the message.
+    transaction.sanitize()?;
+    Ok(transaction)
+}
+
+/// Fetches a confirmed transaction, legacy or v0, in a form that can be
+/// decoded into a `VersionedTransaction`.
+pub fn get_transaction(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta, Box<dyn Error>> {
+    Ok(rpc_client.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(rpc_client.commitment()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?)
 }
@@ -0,0 +1,67 @@
+use solana_address_lookup_table_interface::state::{AddressLookupTable, LookupTableMeta};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{clock::Slot, message::AddressLookupTableAccount, pubkey::Pubkey};
+use std::error::Error;
+use std::fs;
+use std::str::FromStr;
+
+/// Lookup tables created by the `lookup_table` script.  The transaction
+/// sender uses every active table listed here.
+pub const LOOKUP_TABLES_PATH: &str = "./config/lookup_tables.txt";
+
+pub fn saved_lookup_tables() -> Vec<Pubkey> {
+    fs::read_to_string(LOOKUP_TABLES_PATH)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| Pubkey::from_str(line.trim()).ok())
+        .collect()
+}
+
+pub fn save_lookup_table(address: &Pubkey) {
+    let mut tables = saved_lookup_tables();
+    if !tables.contains(address) {
+        tables.push(*address);
+        write_lookup_tables(&tables);
+    }
+}
+
+pub fn forget_lookup_table(address: &Pubkey) {
+    let mut tables = saved_lookup_tables();
+    tables.retain(|table| table != address);
+    write_lookup_tables(&tables);
+}
+
+fn write_lookup_tables(tables: &[Pubkey]) {
+    let contents: String = tables.iter().map(|table| format!("{}\n", table)).collect();
+    fs::write(LOOKUP_TABLES_PATH, contents).expect("Failed to write lookup tables file");
+}
+
+/// Fetches a lookup table, returning its metadata and its addresses.
+pub fn fetch_lookup_table(
+    rpc_client: &RpcClient,
+    address: &Pubkey,
+) -> Result<(LookupTableMeta, AddressLookupTableAccount), Box<dyn Error>> {
+    let account = rpc_client.get_account(address)?;
+    let table = AddressLookupTable::deserialize(&account.data)?;
+    Ok((
+        table.meta,
+        AddressLookupTableAccount {
+            key: *address,
+            addresses: table.addresses.to_vec(),
+        },
+    ))
+}
+
+/// Fetches the tables that can still be used for lookups.  Tables that are
+/// missing or deactivated are skipped.
+pub fn active_lookup_tables(
+    rpc_client: &RpcClient,
+    addresses: &[Pubkey],
+) -> Vec<AddressLookupTableAccount> {
+    addresses
+        .iter()
+        .filter_map(|address| fetch_lookup_table(rpc_client, address).ok())
+        .filter(|(meta, _)| meta.deactivation_slot == Slot::MAX)
+        .map(|(_, table)| table)
+        .collect()
+}
@@ -0,0 +1,81 @@
+use crate::get_transaction;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_sdk::{
+    clock::UnixTimestamp, instruction::Instruction, pubkey::Pubkey, signature::Signature,
+    transaction::VersionedTransaction,
+};
+use std::error::Error;
+use std::str::FromStr;
+
+/// Builds a memo instruction.
+///
+/// Every account in `signers` must sign the transaction, the memo program
+/// checks it.  Pass an empty slice for an unsigned memo.
+pub fn memo_instruction(text: &str, signers: &[&Pubkey]) -> Instruction {
+    spl_memo::build_memo(text.as_bytes(), signers)
+}
+
+pub fn is_memo_program(program_id: &Pubkey) -> bool {
+    *program_id == spl_memo::id() || *program_id == spl_memo::v1::id()
+}
+
+/// Texts of all the top level memo instructions in `transaction`.
+pub fn memos_in_transaction(transaction: &VersionedTransaction) -> Vec<String> {
+    let account_keys = transaction.message.static_account_keys();
+    transaction
+        .message
+        .instructions()
+        .iter()
+        .filter(|instruction| {
+            account_keys
+                .get(instruction.program_id_index as usize)
+                .is_some_and(is_memo_program)
+        })
+        .map(|instruction| String::from_utf8_lossy(&instruction.data).into_owned())
+        .collect()
+}
+
+pub struct AddressMemo {
+    pub signature: Signature,
+    pub slot: u64,
+    pub block_time: Option<UnixTimestamp>,
+    pub failed: bool,
+    pub memos: Vec<String>,
+}
+
+/// Reads the memos attached to the most recent `limit` transactions that
+/// mention `address`, newest first.
+pub fn read_memos(
+    rpc_client: &RpcClient,
+    address: &Pubkey,
+    limit: usize,
+) -> Result<Vec<AddressMemo>, Box<dyn Error>> {
+    let statuses = rpc_client.get_signatures_for_address_with_config(
+        address,
+        GetConfirmedSignaturesForAddress2Config {
+            limit: Some(limit),
+            ..GetConfirmedSignaturesForAddress2Config::default()
+        },
+    )?;
+
+    let mut result = Vec::new();
+    // The RPC already tells us which transactions have memos, only those
+    // need to be downloaded.
+    for status in statuses.into_iter().filter(|status| status.memo.is_some()) {
+        let signature =
+            Signature::from_str(&status.signature).expect("RPC returns valid signatures");
+        let transaction = get_transaction(rpc_client, &signature)?;
+        let Some(decoded) = transaction.transaction.transaction.decode() else {
+            continue;
+        };
+
+        result.push(AddressMemo {
+            signature,
+            slot: status.slot,
+            block_time: status.block_time,
+            failed: status.err.is_some(),
+            memos: memos_in_transaction(&decoded),
+        });
+    }
+    Ok(result)
+}
@@ -0,0 +1,131 @@
+use mpl_token_metadata::accounts::{MasterEdition, Metadata};
+use mpl_token_metadata::instructions::{
+    CreateMasterEditionV3Builder, CreateMetadataAccountV3Builder, VerifyCollectionV1Builder,
+};
+use mpl_token_metadata::types::{Collection, CollectionDetails, DataV2};
+use serde::Deserialize;
+use solana_sdk::{
+    instruction::Instruction, program_pack::Pack, pubkey::Pubkey, system_instruction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use spl_token::instruction::{initialize_mint2, mint_to};
+use spl_token::state::Mint;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// On-chain metadata of an NFT, as read from a JSON file.
+///
+/// `uri` points to the off-chain JSON with the image and attributes.  Without
+/// `recipient` the NFT stays in our wallet.
+#[derive(Deserialize)]
+pub struct NftMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    #[serde(default)]
+    pub seller_fee_basis_points: u16,
+    pub recipient: Option<String>,
+}
+
+impl NftMetadata {
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn recipient(&self) -> Result<Option<Pubkey>, Box<dyn Error>> {
+        match &self.recipient {
+            Some(recipient) => Ok(Some(recipient.parse()?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Instructions that mint a 0-decimal, supply-1 token to `recipient` and
+/// create its metadata and master edition.
+///
+/// The master edition takes over the mint and freeze authorities, so no more
+/// tokens can ever be minted.  With `collection` the NFT points to that
+/// collection, it still has to be verified with
+/// [`verify_collection_instruction`].  With `is_collection` the NFT is itself a
+/// sized collection other NFTs can be verified into.
+pub fn mint_nft_instructions(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    metadata: &NftMetadata,
+    mint_rent: u64,
+    collection: Option<&Pubkey>,
+    is_collection: bool,
+) -> Vec<Instruction> {
+    let token_account = get_associated_token_address(recipient, mint);
+    let (metadata_address, _) = Metadata::find_pda(mint);
+    let (edition_address, _) = MasterEdition::find_pda(mint);
+
+    let data = DataV2 {
+        name: metadata.name.clone(),
+        symbol: metadata.symbol.clone(),
+        uri: metadata.uri.clone(),
+        seller_fee_basis_points: metadata.seller_fee_basis_points,
+        creators: None,
+        collection: collection.map(|collection| Collection {
+            verified: false,
+            key: *collection,
+        }),
+        uses: None,
+    };
+
+    let mut create_metadata = CreateMetadataAccountV3Builder::new();
+    create_metadata
+        .metadata(metadata_address)
+        .mint(*mint)
+        .mint_authority(*payer)
+        .payer(*payer)
+        .update_authority(*payer, true)
+        .data(data)
+        .is_mutable(true);
+    if is_collection {
+        create_metadata.collection_details(CollectionDetails::V1 { size: 0 });
+    }
+
+    vec![
+        system_instruction::create_account(
+            payer,
+            mint,
+            mint_rent,
+            Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        initialize_mint2(&spl_token::id(), mint, payer, Some(payer), 0).unwrap(),
+        create_associated_token_account_idempotent(payer, recipient, mint, &spl_token::id()),
+        mint_to(&spl_token::id(), mint, &token_account, payer, &[], 1).unwrap(),
+        create_metadata.instruction(),
+        CreateMasterEditionV3Builder::new()
+            .edition(edition_address)
+            .mint(*mint)
+            .update_authority(*payer)
+            .mint_authority(*payer)
+            .payer(*payer)
+            .metadata(metadata_address)
+            .max_supply(0)
+            .instruction(),
+    ]
+}
+
+/// Marks `mint` as a verified member of `collection`.  `authority` must be the
+/// update authority of the collection NFT.
+pub fn verify_collection_instruction(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    collection: &Pubkey,
+) -> Instruction {
+    VerifyCollectionV1Builder::new()
+        .authority(*authority)
+        .metadata(Metadata::find_pda(mint).0)
+        .collection_mint(*collection)
+        .collection_metadata(Some(Metadata::find_pda(collection).0))
+        .collection_master_edition(Some(MasterEdition::find_pda(collection).0))
+        .instruction()
+}
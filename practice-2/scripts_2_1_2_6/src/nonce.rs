@@ -0,0 +1,147 @@
+use solana_client::nonce_utils;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::Hash, instruction::Instruction, message::Message, nonce::state::Data, pubkey::Pubkey,
+    system_instruction::SystemInstruction, system_program, transaction::Transaction,
+};
+use std::error::Error;
+
+/// Reads the current state of a nonce account.
+pub fn get_nonce_data(
+    rpc_client: &RpcClient,
+    nonce_account: &Pubkey,
+) -> Result<Data, nonce_utils::Error> {
+    let account = nonce_utils::get_account(rpc_client, nonce_account)?;
+    nonce_utils::data_from_account(&account)
+}
+
+/// Builds an unsigned transaction that uses a durable nonce instead of a
+/// recent blockhash.
+///
+/// `advance_nonce_account` is inserted as the first instruction, so the
+/// transaction stays valid until the nonce is advanced and can be signed
+/// offline and submitted much later.
+pub fn build_durable_transaction(
+    instructions: Vec<Instruction>,
+    payer: &Pubkey,
+    nonce_account: &Pubkey,
+    nonce_authority: &Pubkey,
+    nonce: Hash,
+) -> Transaction {
+    let mut message =
+        Message::new_with_nonce(instructions, Some(payer), nonce_account, nonce_authority);
+    message.recent_blockhash = nonce;
+    Transaction::new_unsigned(message)
+}
+
+/// Returns the nonce account used by a durable transaction, if any.
+///
+/// Fails when the first instruction refers to accounts the transaction does
+/// not have, as a truncated or corrupted transaction file would.
+pub fn durable_nonce_account(transaction: &Transaction) -> Result<Option<Pubkey>, Box<dyn Error>> {
+    let account_keys = &transaction.message.account_keys;
+    let account_key = |index: u8| {
+        account_keys
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| format!("Account index {} is out of range", index))
+    };
+
+    let Some(advance_nonce) = transaction.message.instructions.first() else {
+        return Ok(None);
+    };
+    if !system_program::check_id(&account_key(advance_nonce.program_id_index)?) {
+        return Ok(None);
+    }
+    match bincode::deserialize(&advance_nonce.data) {
+        Ok(SystemInstruction::AdvanceNonceAccount) => {}
+        _ => return Ok(None),
+    }
+    let nonce_index = *advance_nonce
+        .accounts
+        .first()
+        .ok_or("advance_nonce_account has no nonce account")?;
+    Ok(Some(account_key(nonce_index)?))
+}
+
+/// Signers whose signatures are still missing from `transaction`.
+///
+/// Fails when the transaction has fewer keys or signatures than its header
+/// requires.
+pub fn missing_signers(transaction: &Transaction) -> Result<Vec<Pubkey>, Box<dyn Error>> {
+    let required = transaction.message.header.num_required_signatures as usize;
+    let signers = transaction
+        .message
+        .account_keys
+        .get(..required)
+        .ok_or("Transaction has fewer account keys than required signers")?;
+    if transaction.signatures.len() != required {
+        return Err(format!(
+            "Transaction has {} signatures, {} required",
+            transaction.signatures.len(),
+            required
+        )
+        .into());
+    }
+    Ok(signers
+        .iter()
+        .zip(&transaction.signatures)
+        .filter(|(_, signature)| **signature == Default::default())
+        .map(|(key, _)| *key)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::system_instruction;
+
+    fn durable_transaction(nonce_account: &Pubkey) -> Transaction {
+        let payer = Pubkey::new_unique();
+        build_durable_transaction(
+            vec![system_instruction::transfer(
+                &payer,
+                &Pubkey::new_unique(),
+                1,
+            )],
+            &payer,
+            nonce_account,
+            &payer,
+            Hash::new_unique(),
+        )
+    }
+
+    #[test]
+    fn reads_durable_transactions() {
+        let nonce_account = Pubkey::new_unique();
+        let transaction = durable_transaction(&nonce_account);
+        assert_eq!(
+            durable_nonce_account(&transaction).unwrap(),
+            Some(nonce_account)
+        );
+        assert_eq!(
+            missing_signers(&transaction).unwrap(),
+            vec![transaction.message.account_keys[0]]
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_account_indices() {
+        let mut transaction = durable_transaction(&Pubkey::new_unique());
+        transaction.message.instructions[0].accounts[0] = u8::MAX;
+        assert!(durable_nonce_account(&transaction).is_err());
+
+        transaction.message.instructions[0].program_id_index = u8::MAX;
+        assert!(durable_nonce_account(&transaction).is_err());
+    }
+
+    #[test]
+    fn rejects_missing_signatures_and_keys() {
+        let mut transaction = durable_transaction(&Pubkey::new_unique());
+        transaction.signatures.clear();
+        assert!(missing_signers(&transaction).is_err());
+
+        transaction.message.header.num_required_signatures = u8::MAX;
+        assert!(missing_signers(&transaction).is_err());
+    }
+}
@@ -0,0 +1,418 @@
+use crate::get_transaction;
+use crate::memo::memo_instruction;
+use crate::token::{MintInfo, format_amount, parse_ui_amount, transfer_instructions};
+use qrcode::QrCode;
+use qrcode::render::unicode::Dense1x2;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signature,
+    system_instruction,
+};
+use solana_transaction_status_client_types::{UiLoadedAddresses, UiTransactionTokenBalance};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::error::Error;
+use std::str::FromStr;
+use url::Url;
+
+/// Decimals of SOL amounts in a transfer request.
+pub const SOL_DECIMALS: u8 = 9;
+
+/// A Solana Pay transfer request, see
+/// https://docs.solanapay.com/spec#specification-transfer-request
+///
+/// `amount` is kept as the decimal string of the URL, it is only converted to
+/// minor units once the decimals of the mint are known.
+#[derive(Default)]
+pub struct TransferRequest {
+    pub recipient: Pubkey,
+    pub amount: Option<String>,
+    pub spl_token: Option<Pubkey>,
+    pub references: Vec<Pubkey>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+    pub memo: Option<String>,
+}
+
+impl TransferRequest {
+    pub fn new(recipient: Pubkey) -> Self {
+        TransferRequest {
+            recipient,
+            ..TransferRequest::default()
+        }
+    }
+
+    /// Encodes the request as a `solana:` URL.
+    pub fn to_url(&self) -> String {
+        let mut url = Url::parse(&format!("solana:{}", self.recipient)).unwrap();
+        {
+            let mut query = url.query_pairs_mut();
+            if let Some(amount) = &self.amount {
+                query.append_pair("amount", amount);
+            }
+            if let Some(spl_token) = &self.spl_token {
+                query.append_pair("spl-token", &spl_token.to_string());
+            }
+            for reference in &self.references {
+                query.append_pair("reference", &reference.to_string());
+            }
+            for (name, value) in [
+                ("label", &self.label),
+                ("message", &self.message),
+                ("memo", &self.memo),
+            ] {
+                if let Some(value) = value {
+                    query.append_pair(name, value);
+                }
+            }
+        }
+        // An empty query would leave a trailing `?`.
+        if url.query() == Some("") {
+            url.set_query(None);
+        }
+        url.to_string()
+    }
+
+    /// Parses a `solana:` transfer request URL.
+    pub fn parse(url: &str) -> Result<Self, Box<dyn Error>> {
+        let url = Url::parse(url)?;
+        if url.scheme() != "solana" {
+            return Err(format!("{} is not a solana: URL", url).into());
+        }
+        let recipient = Pubkey::from_str(url.path())
+            .map_err(|_| format!("Invalid recipient: {}", url.path()))?;
+
+        let mut request = TransferRequest::new(recipient);
+        for (name, value) in url.query_pairs() {
+            let value = value.into_owned();
+            match name.as_ref() {
+                "amount" => {
+                    // The spec only allows plain decimals, no signs or
+                    // exponents.  The number of decimals is checked against
+                    // the mint later.
+                    let (whole, fraction) = value.split_once('.').unwrap_or((&value, ""));
+                    if whole.is_empty()
+                        || !(whole.chars().chain(fraction.chars())).all(|c| c.is_ascii_digit())
+                    {
+                        return Err(format!("Invalid amount: {}", value).into());
+                    }
+                    request.amount = Some(value);
+                }
+                "spl-token" => {
+                    request.spl_token = Some(
+                        Pubkey::from_str(&value)
+                            .map_err(|_| format!("Invalid spl-token: {}", value))?,
+                    )
+                }
+                "reference" => request.references.push(
+                    Pubkey::from_str(&value)
+                        .map_err(|_| format!("Invalid reference: {}", value))?,
+                ),
+                "label" => request.label = Some(value),
+                "message" => request.message = Some(value),
+                "memo" => request.memo = Some(value),
+                // Unknown fields are allowed by the spec.
+                _ => {}
+            }
+        }
+        Ok(request)
+    }
+
+    /// The requested amount in minor units, for a mint with `decimals`.
+    pub fn amount_in(&self, decimals: u8) -> Result<Option<u64>, Box<dyn Error>> {
+        match &self.amount {
+            None => Ok(None),
+            Some(amount) => parse_ui_amount(amount, decimals).map(Some).ok_or_else(|| {
+                format!("Invalid amount {} for {} decimals", amount, decimals).into()
+            }),
+        }
+    }
+}
+
+/// Instructions that pay `amount` minor units of a transfer request, in SOL
+/// or in `mint` when the request names an `spl-token`.
+///
+/// As the spec requires, the memo comes immediately before the transfer, after
+/// the recipient token account is created, and the references are added to
+/// the transfer as read-only accounts so the payment can be found by them.
+pub fn pay_instructions(
+    payer: &Pubkey,
+    request: &TransferRequest,
+    mint: Option<&MintInfo>,
+    amount: u64,
+    create_recipient_account: bool,
+) -> Vec<Instruction> {
+    let mut instructions = match mint {
+        Some(mint) => transfer_instructions(
+            payer,
+            &request.recipient,
+            mint,
+            amount,
+            create_recipient_account,
+        ),
+        None => vec![system_instruction::transfer(
+            payer,
+            &request.recipient,
+            amount,
+        )],
+    };
+    let mut transfer = instructions.pop().unwrap();
+    transfer.accounts.extend(
+        request
+            .references
+            .iter()
+            .map(|reference| AccountMeta::new_readonly(*reference, false)),
+    );
+    if let Some(memo) = &request.memo {
+        instructions.push(memo_instruction(memo, &[payer]));
+    }
+    instructions.push(transfer);
+    instructions
+}
+
+/// Finds the oldest transaction that mentions `reference`, which is the
+/// payment of the request that used it.
+pub fn find_reference(
+    rpc_client: &RpcClient,
+    reference: &Pubkey,
+) -> Result<Option<Signature>, Box<dyn Error>> {
+    let statuses = rpc_client.get_signatures_for_address_with_config(
+        reference,
+        GetConfirmedSignaturesForAddress2Config {
+            limit: Some(1000),
+            ..GetConfirmedSignaturesForAddress2Config::default()
+        },
+    )?;
+    match statuses.last() {
+        Some(status) => Ok(Some(Signature::from_str(&status.signature)?)),
+        None => Ok(None),
+    }
+}
+
+/// Checks that the confirmed transaction `signature` paid at least `amount`
+/// minor units to the recipient of `request`.
+pub fn validate_transfer(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+    request: &TransferRequest,
+    mint: Option<&MintInfo>,
+    amount: u64,
+) -> Result<(), Box<dyn Error>> {
+    let transaction = get_transaction(rpc_client, signature)?.transaction;
+    let meta = transaction.meta.ok_or("The transaction has no status")?;
+    if let Some(err) = meta.err {
+        return Err(format!("The transaction failed: {}", err).into());
+    }
+    let decoded = transaction
+        .transaction
+        .decode()
+        .ok_or("Failed to decode the transaction")?;
+
+    // Accounts loaded from lookup tables follow the static ones.
+    let mut account_keys = decoded.message.static_account_keys().to_vec();
+    if let Some(loaded) = Option::<UiLoadedAddresses>::from(meta.loaded_addresses) {
+        for address in loaded.writable.iter().chain(&loaded.readonly) {
+            account_keys.push(Pubkey::from_str(address)?);
+        }
+    }
+
+    let received = match mint {
+        None => {
+            let index = account_keys
+                .iter()
+                .position(|key| *key == request.recipient)
+                .ok_or("The recipient is not part of the transaction")?;
+            meta.post_balances[index].saturating_sub(meta.pre_balances[index])
+        }
+        Some(mint) => {
+            let token_account = get_associated_token_address_with_program_id(
+                &request.recipient,
+                &mint.address,
+                &mint.token_program,
+            );
+            let index = account_keys
+                .iter()
+                .position(|key| *key == token_account)
+                .ok_or("The recipient token account is not part of the transaction")?;
+            let balance = |balances: Option<Vec<UiTransactionTokenBalance>>| -> u64 {
+                balances
+                    .unwrap_or_default()
+                    .iter()
+                    .find(|balance| balance.account_index as usize == index)
+                    .and_then(|balance| balance.ui_token_amount.amount.parse().ok())
+                    .unwrap_or(0)
+            };
+            balance(meta.post_token_balances.into())
+                .saturating_sub(balance(meta.pre_token_balances.into()))
+        }
+    };
+
+    if received < amount {
+        return Err(format!(
+            "The recipient received {}, {} were requested",
+            format_request_amount(received, mint),
+            format_request_amount(amount, mint)
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Formats an amount of SOL or of a mint for display.
+pub fn format_request_amount(amount: u64, mint: Option<&MintInfo>) -> String {
+    match mint {
+        Some(mint) => format!(
+            "{} of {}",
+            format_amount(amount, mint.mint.decimals),
+            mint.address
+        ),
+        None => format!(
+            "{} SOL",
+            format_amount(amount, SOL_DECIMALS)
+                .trim_end_matches('0')
+                .trim_end_matches('.')
+        ),
+    }
+}
+
+/// Renders `data` as a QR code for the terminal, two rows per line.
+pub fn qr_code(data: &str) -> Result<String, Box<dyn Error>> {
+    let code = QrCode::new(data.as_bytes())?;
+    // Light modules are drawn as blocks, which reads correctly on the usual
+    // dark terminal background.
+    Ok(code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token_2022::state::Mint;
+
+    const RECIPIENT: &str = "mvines9iiHiQTysrwkJjGf2gb9Ex9jXJX8ns3qwf2kN";
+    const USDC: &str = "EPjFWdd5AufLSaQj1u2UVBTHSLHMNA6WyoJC3u1kgh1v";
+
+    fn recipient() -> Pubkey {
+        Pubkey::from_str(RECIPIENT).unwrap()
+    }
+
+    #[test]
+    fn round_trips_a_full_request() {
+        let request = TransferRequest {
+            amount: Some("1.01".to_string()),
+            spl_token: Some(Pubkey::from_str(USDC).unwrap()),
+            references: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            label: Some("Michael & Sons".to_string()),
+            message: Some("Thanks for all the fish".to_string()),
+            memo: Some("OrderId=12345?x".to_string()),
+            ..TransferRequest::new(recipient())
+        };
+        let url = request.to_url();
+        let parsed = TransferRequest::parse(&url).unwrap();
+
+        assert_eq!(parsed.recipient, request.recipient);
+        assert_eq!(parsed.amount, request.amount);
+        assert_eq!(parsed.spl_token, request.spl_token);
+        assert_eq!(parsed.references, request.references);
+        assert_eq!(parsed.label, request.label);
+        assert_eq!(parsed.message, request.message);
+        assert_eq!(parsed.memo, request.memo);
+        assert_eq!(parsed.to_url(), url);
+    }
+
+    #[test]
+    fn encodes_a_bare_recipient() {
+        let url = TransferRequest::new(recipient()).to_url();
+        assert_eq!(url, format!("solana:{}", RECIPIENT));
+        assert!(TransferRequest::parse(&url).unwrap().amount.is_none());
+    }
+
+    #[test]
+    fn parses_percent_encoded_fields() {
+        let url = format!(
+            "solana:{}?label=Michael%20%26%20Sons&memo=caf%C3%A9%3Dyes&message=a+b",
+            RECIPIENT
+        );
+        let request = TransferRequest::parse(&url).unwrap();
+        assert_eq!(request.label.as_deref(), Some("Michael & Sons"));
+        assert_eq!(request.memo.as_deref(), Some("café=yes"));
+        assert_eq!(request.message.as_deref(), Some("a b"));
+    }
+
+    #[test]
+    fn parses_multiple_references_in_order() {
+        let references = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let url = format!(
+            "solana:{}?reference={}&reference={}",
+            RECIPIENT, references[0], references[1]
+        );
+        let request = TransferRequest::parse(&url).unwrap();
+        assert_eq!(request.references, references);
+    }
+
+    #[test]
+    fn rejects_malformed_amounts() {
+        for amount in [".5", "-1", "+1", "1e3", "1.5e2", "0x10", "1,5", ""] {
+            let url = format!("solana:{}?amount={}", RECIPIENT, amount);
+            assert!(TransferRequest::parse(&url).is_err(), "{}", amount);
+        }
+    }
+
+    #[test]
+    fn checks_amount_decimals_against_the_mint() {
+        let url = format!("solana:{}?amount=0.123", RECIPIENT);
+        let request = TransferRequest::parse(&url).unwrap();
+        assert_eq!(request.amount_in(3).unwrap(), Some(123));
+        assert_eq!(request.amount_in(9).unwrap(), Some(123_000_000));
+        assert!(request.amount_in(2).is_err());
+        assert_eq!(
+            TransferRequest::new(recipient()).amount_in(2).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_other_schemes_and_recipients() {
+        let url = format!("https://example.com/{}?amount=1", RECIPIENT);
+        assert!(TransferRequest::parse(&url).is_err());
+        assert!(TransferRequest::parse("solana:not-a-pubkey").is_err());
+        let url = format!("solana:{}?reference=nope", RECIPIENT);
+        assert!(TransferRequest::parse(&url).is_err());
+    }
+
+    #[test]
+    fn puts_the_memo_right_before_the_transfer() {
+        let payer = Pubkey::new_unique();
+        let mint = MintInfo {
+            address: Pubkey::from_str(USDC).unwrap(),
+            token_program: spl_token::id(),
+            mint: Mint {
+                decimals: 6,
+                is_initialized: true,
+                ..Mint::default()
+            },
+        };
+        let reference = Pubkey::new_unique();
+        let request = TransferRequest {
+            spl_token: Some(mint.address),
+            references: vec![reference],
+            memo: Some("order 1".to_string()),
+            ..TransferRequest::new(recipient())
+        };
+
+        let instructions = pay_instructions(&payer, &request, Some(&mint), 1, true);
+        let programs: Vec<Pubkey> = instructions
+            .iter()
+            .map(|instruction| instruction.program_id)
+            .collect();
+        assert_eq!(programs.len(), 3);
+        assert_eq!(programs[0], spl_associated_token_account::id());
+        assert!(crate::memo::is_memo_program(&programs[1]));
+        assert_eq!(programs[2], spl_token::id());
+        assert_eq!(instructions[2].accounts.last().unwrap().pubkey, reference);
+    }
+}
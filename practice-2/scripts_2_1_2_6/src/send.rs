@@ -0,0 +1,504 @@
+use crate::lookup_table::{active_lookup_tables, saved_lookup_tables};
+use solana_client::client_error::ClientError;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::Instruction,
+    message::{AddressLookupTableAccount, CompileError, Message, VersionedMessage, v0},
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{SignerError, signers::Signers},
+    transaction::{TransactionError, VersionedTransaction},
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::thread::sleep;
+use std::time::Duration;
+
+/// Compute units a transaction may use at most.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+#[derive(Debug)]
+pub enum SendError {
+    Client(Box<ClientError>),
+    Signing(SignerError),
+    /// The instructions could not be compiled into a v0 message.
+    Compile(CompileError),
+    /// The transaction failed in simulation, nothing was sent.
+    Simulation {
+        error: TransactionError,
+        logs: Vec<String>,
+    },
+    /// The transaction landed, but failed.
+    Transaction(TransactionError),
+    /// Every attempt expired before the transaction was confirmed.
+    Expired {
+        attempts: usize,
+    },
+    /// The transaction was only simulated, see [`TransactionSender::new`].
+    /// Callers treat it as neither a success nor a failure.
+    DryRun,
+}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendError::Client(err) => write!(f, "{}", err),
+            SendError::Signing(err) => write!(f, "failed to sign: {}", err),
+            SendError::Compile(err) => write!(f, "failed to compile message: {}", err),
+            SendError::Simulation { error, logs } => {
+                write!(f, "simulation failed: {}", error)?;
+                for log in logs {
+                    write!(f, "\n  {}", log)?;
+                }
+                Ok(())
+            }
+            SendError::Transaction(err) => write!(f, "transaction failed: {}", err),
+            SendError::Expired { attempts } => {
+                write!(f, "blockhash expired {} times, giving up", attempts)
+            }
+            SendError::DryRun => write!(f, "dry run, nothing was sent"),
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+impl From<ClientError> for SendError {
+    fn from(err: ClientError) -> Self {
+        SendError::Client(Box::new(err))
+    }
+}
+
+impl From<SignerError> for SendError {
+    fn from(err: SignerError) -> Self {
+        SendError::Signing(err)
+    }
+}
+
+impl From<CompileError> for SendError {
+    fn from(err: CompileError) -> Self {
+        SendError::Compile(err)
+    }
+}
+
+/// Sends transactions the same way from every script:
+///
+/// 1. simulates the instructions, failing with the program logs if the
+///    simulation fails,
+/// 2. sets the compute unit limit to the simulated usage plus a margin,
+/// 3. adds a priority fee based on `get_recent_prioritization_fees` for the
+///    writable accounts,
+/// 4. signs with a fresh blockhash and, if that blockhash expires before the
+///    transaction is confirmed, re-signs and resends.
+///
+/// When address lookup tables are available, a v0 `VersionedTransaction` is
+/// built and the accounts found in the tables are loaded from them.  By
+/// default the active tables from [`LOOKUP_TABLES_PATH`] are used, otherwise
+/// a legacy message is sent.
+///
+/// In dry-run mode, which every script enables with `--dry-run`, nothing is
+/// sent: the signed transaction is simulated and described instead, see
+/// [`dry_run`], and `send()` fails with [`SendError::DryRun`].
+///
+/// [`LOOKUP_TABLES_PATH`]: crate::lookup_table::LOOKUP_TABLES_PATH
+pub struct TransactionSender<'a> {
+    rpc_client: &'a RpcClient,
+    compute_unit_margin_percent: u64,
+    max_attempts: usize,
+    lookup_tables: Option<Vec<Pubkey>>,
+    dry_run: bool,
+}
+
+impl<'a> TransactionSender<'a> {
+    /// With `dry_run`, usually the `--dry-run` flag of the script, the
+    /// transactions are only simulated.
+    pub fn new(rpc_client: &'a RpcClient, dry_run: bool) -> Self {
+        TransactionSender {
+            rpc_client,
+            compute_unit_margin_percent: 10,
+            max_attempts: 3,
+            lookup_tables: None,
+            dry_run,
+        }
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Uses these lookup tables instead of the saved ones.  An empty list
+    /// forces a legacy transaction.
+    pub fn lookup_tables(mut self, addresses: Vec<Pubkey>) -> Self {
+        self.lookup_tables = Some(addresses);
+        self
+    }
+
+    pub fn compute_unit_margin_percent(mut self, percent: u64) -> Self {
+        self.compute_unit_margin_percent = percent;
+        self
+    }
+
+    pub fn max_attempts(mut self, attempts: usize) -> Self {
+        self.max_attempts = attempts;
+        self
+    }
+
+    pub fn send<T: Signers + ?Sized>(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        signers: &T,
+    ) -> Result<Signature, SendError> {
+        self.send_tracked(instructions, payer, signers, |_, _| {})
+    }
+
+    /// Like [`send`](Self::send), but calls `on_signed` with the signature
+    /// and the last valid block height of every attempt before it is sent.
+    /// Callers record them to find out later whether an attempt landed, as
+    /// an error while confirming doesn't mean it didn't.
+    pub fn send_tracked<T: Signers + ?Sized>(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        signers: &T,
+        mut on_signed: impl FnMut(&Signature, u64),
+    ) -> Result<Signature, SendError> {
+        let lookup_tables = match &self.lookup_tables {
+            Some(addresses) => active_lookup_tables(self.rpc_client, addresses),
+            None => active_lookup_tables(self.rpc_client, &saved_lookup_tables()),
+        };
+
+        let priority_fee = self.priority_fee(instructions, payer)?;
+        let compute_units = match self.simulate(instructions, payer, priority_fee, &lookup_tables) {
+            // A failing transaction is still shown in full on a dry run.
+            Err(SendError::Simulation { .. }) if self.dry_run => MAX_COMPUTE_UNIT_LIMIT,
+            result => result?,
+        };
+        let instructions = with_compute_budget(instructions, compute_units, priority_fee);
+
+        if self.dry_run {
+            let (blockhash, _) = self
+                .rpc_client
+                .get_latest_blockhash_with_commitment(self.rpc_client.commitment())?;
+            let message = compile_message(&instructions, payer, &lookup_tables, blockhash)?;
+            let transaction = VersionedTransaction::try_new(message, signers)?;
+            dry_run(self.rpc_client, &transaction)?;
+            return Err(SendError::DryRun);
+        }
+
+        for attempt in 1..=self.max_attempts {
+            let (blockhash, last_valid_block_height) = self
+                .rpc_client
+                .get_latest_blockhash_with_commitment(self.rpc_client.commitment())?;
+
+            let message = compile_message(&instructions, payer, &lookup_tables, blockhash)?;
+            let transaction = VersionedTransaction::try_new(message, signers)?;
+            on_signed(&transaction.signatures[0], last_valid_block_height);
+
+            // Preflight already happened in `simulate()`.
+            let signature = self.rpc_client.send_transaction_with_config(
+                &transaction,
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    ..RpcSendTransactionConfig::default()
+                },
+            )?;
+
+            if self.confirm(&signature, last_valid_block_height)? {
+                return Ok(signature);
+            }
+
+            println!(
+                "⏳ Blockhash expired before confirmation (attempt {}/{}), re-signing",
+                attempt, self.max_attempts
+            );
+        }
+
+        Err(SendError::Expired {
+            attempts: self.max_attempts,
+        })
+    }
+
+    /// Runs the transaction through `simulate_transaction` and returns the
+    /// compute unit limit to request.
+    fn simulate(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        priority_fee: u64,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<u32, SendError> {
+        let instructions = with_compute_budget(instructions, MAX_COMPUTE_UNIT_LIMIT, priority_fee);
+        // The RPC replaces the blockhash and skips signature verification.
+        let message = compile_message(&instructions, payer, lookup_tables, Hash::default())?;
+        let transaction = VersionedTransaction {
+            signatures: vec![
+                Signature::default();
+                message.header().num_required_signatures as usize
+            ],
+            message,
+        };
+
+        let result = self
+            .rpc_client
+            .simulate_transaction_with_config(
+                &transaction,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    commitment: Some(self.rpc_client.commitment()),
+                    ..RpcSimulateTransactionConfig::default()
+                },
+            )?
+            .value;
+
+        if let Some(error) = result.err {
+            return Err(SendError::Simulation {
+                error,
+                logs: result.logs.unwrap_or_default(),
+            });
+        }
+
+        let units_consumed = result
+            .units_consumed
+            .unwrap_or(MAX_COMPUTE_UNIT_LIMIT as u64);
+        let with_margin = units_consumed * (100 + self.compute_unit_margin_percent) / 100;
+        Ok(with_margin.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32)
+    }
+
+    /// Median of the recent non-zero prioritization fees paid for the
+    /// writable accounts of the transaction, in micro-lamports per compute
+    /// unit.
+    fn priority_fee(&self, instructions: &[Instruction], payer: &Pubkey) -> Result<u64, SendError> {
+        let message = Message::new(instructions, Some(payer));
+        let writable_accounts: Vec<Pubkey> = message
+            .account_keys
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| message.is_maybe_writable(*index, None))
+            .map(|(_, key)| *key)
+            .collect();
+
+        let mut fees: Vec<u64> = self
+            .rpc_client
+            .get_recent_prioritization_fees(&writable_accounts)?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .filter(|fee| *fee > 0)
+            .collect();
+
+        if fees.is_empty() {
+            return Ok(0);
+        }
+        fees.sort_unstable();
+        Ok(fees[fees.len() / 2])
+    }
+
+    /// Waits for `signature` to be confirmed.  Returns `false` if the
+    /// blockhash expired first.
+    fn confirm(
+        &self,
+        signature: &Signature,
+        last_valid_block_height: u64,
+    ) -> Result<bool, SendError> {
+        loop {
+            // Read the block height first, so a transaction that lands right
+            // before the blockhash expires is still seen as confirmed.
+            let expired = self.rpc_client.get_block_height()? > last_valid_block_height;
+
+            let status = self
+                .rpc_client
+                .get_signature_status_with_commitment(signature, self.rpc_client.commitment())?;
+            match status {
+                Some(Ok(())) => return Ok(true),
+                Some(Err(err)) => return Err(SendError::Transaction(err)),
+                None if expired => return Ok(false),
+                None => sleep(Duration::from_millis(500)),
+            }
+        }
+    }
+}
+
+/// What became of an attempt recorded through
+/// [`TransactionSender::send_tracked`], from least to most final.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Attempt {
+    /// It failed, or its blockhash expired before it landed.
+    Dropped,
+    /// It may still land or be rolled back.
+    InFlight,
+    Landed,
+}
+
+/// Looks up attempts of earlier runs, given as signature and last valid block
+/// height.
+pub fn check_attempts(
+    rpc_client: &RpcClient,
+    attempts: &[(Signature, u64)],
+) -> Result<HashMap<Signature, Attempt>, SendError> {
+    let mut states = HashMap::new();
+    if attempts.is_empty() {
+        return Ok(states);
+    }
+    // Read the block height first, like `TransactionSender` does, so an
+    // attempt that lands meanwhile isn't taken as expired.
+    let block_height = rpc_client.get_block_height()?;
+    // The RPC looks up at most 256 signatures at once.
+    for chunk in attempts.chunks(256) {
+        let signatures: Vec<Signature> = chunk.iter().map(|(signature, _)| *signature).collect();
+        let statuses = rpc_client
+            .get_signature_statuses_with_history(&signatures)?
+            .value;
+        for ((signature, last_valid_block_height), status) in chunk.iter().zip(statuses) {
+            let state = match status {
+                Some(status) if status.err.is_some() => Attempt::Dropped,
+                Some(status) if status.satisfies_commitment(rpc_client.commitment()) => {
+                    Attempt::Landed
+                }
+                Some(_) => Attempt::InFlight,
+                None if block_height <= *last_valid_block_height => Attempt::InFlight,
+                None => Attempt::Dropped,
+            };
+            states.insert(*signature, state);
+        }
+    }
+    Ok(states)
+}
+
+/// Simulates a signed transaction and prints its accounts, instructions,
+/// compute units, fee and logs.  Fails like [`TransactionSender::send`] if
+/// the simulation fails.
+pub fn dry_run(
+    rpc_client: &RpcClient,
+    transaction: &VersionedTransaction,
+) -> Result<(), SendError> {
+    let message = &transaction.message;
+    let account_keys = message.static_account_keys();
+
+    println!("🧪 Dry run, nothing is sent");
+    match message {
+        VersionedMessage::Legacy(_) => println!("Message: legacy"),
+        VersionedMessage::V0(_) => println!("Message: v0"),
+    }
+    println!("Accounts:");
+    for (index, key) in account_keys.iter().enumerate() {
+        let mut flags = Vec::new();
+        if message.is_signer(index) {
+            flags.push("signer");
+        }
+        if message.is_maybe_writable(index, None) {
+            flags.push("writable");
+        }
+        println!("  {:>2}: {} {}", index, key, flags.join(", "));
+    }
+    for lookup in message.address_table_lookups().unwrap_or_default() {
+        println!(
+            "  from lookup table {}: {} writable, {} readonly",
+            lookup.account_key,
+            lookup.writable_indexes.len(),
+            lookup.readonly_indexes.len()
+        );
+    }
+
+    println!("Instructions:");
+    let describe = |index: u8| match account_keys.get(index as usize) {
+        Some(key) => key.to_string(),
+        // Accounts loaded from lookup tables come after the static ones.
+        None => format!("lookup #{}", index as usize - account_keys.len()),
+    };
+    for (index, instruction) in message.instructions().iter().enumerate() {
+        println!(
+            "  {:>2}: program {}",
+            index,
+            describe(instruction.program_id_index)
+        );
+        for account in &instruction.accounts {
+            println!("        {}", describe(*account));
+        }
+        let data: String = instruction
+            .data
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        println!("        data ({} bytes): {}", instruction.data.len(), data);
+    }
+
+    let fee = match message {
+        VersionedMessage::Legacy(message) => rpc_client.get_fee_for_message(message),
+        VersionedMessage::V0(message) => rpc_client.get_fee_for_message(message),
+    };
+    match fee {
+        Ok(fee) => println!("Fee: {} lamports", fee),
+        Err(err) => println!("Fee: unknown ({})", err),
+    }
+
+    let result = rpc_client
+        .simulate_transaction_with_config(
+            transaction,
+            RpcSimulateTransactionConfig {
+                sig_verify: true,
+                commitment: Some(rpc_client.commitment()),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?
+        .value;
+    if let Some(units) = result.units_consumed {
+        println!("Compute units: {}", units);
+    }
+
+    let logs = result.logs.unwrap_or_default();
+    if let Some(error) = result.err {
+        return Err(SendError::Simulation { error, logs });
+    }
+    println!("Logs:");
+    for log in &logs {
+        println!("  {}", log);
+    }
+    println!("✅ Simulation succeeded");
+    Ok(())
+}
+
+/// Builds a v0 message when there are lookup tables to use, and a legacy one
+/// otherwise.
+pub fn compile_message(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> Result<VersionedMessage, CompileError> {
+    if lookup_tables.is_empty() {
+        return Ok(VersionedMessage::Legacy(Message::new_with_blockhash(
+            instructions,
+            Some(payer),
+            &blockhash,
+        )));
+    }
+
+    Ok(VersionedMessage::V0(v0::Message::try_compile(
+        payer,
+        instructions,
+        lookup_tables,
+        blockhash,
+    )?))
+}
+
+/// Prepends compute budget instructions to `instructions`.
+pub fn with_compute_budget(
+    instructions: &[Instruction],
+    compute_unit_limit: u32,
+    micro_lamports_per_compute_unit: u64,
+) -> Vec<Instruction> {
+    let mut result = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        compute_unit_limit,
+    )];
+    if micro_lamports_per_compute_unit > 0 {
+        result.push(ComputeBudgetInstruction::set_compute_unit_price(
+            micro_lamports_per_compute_unit,
+        ));
+    }
+    result.extend_from_slice(instructions);
+    result
+}
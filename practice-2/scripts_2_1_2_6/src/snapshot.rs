@@ -0,0 +1,109 @@
+use crate::token::{MintInfo, format_amount, get_token_accounts_by_mint};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{clock::Slot, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Balances of one owner, summed over all of their token accounts.
+#[derive(Serialize)]
+pub struct Holder {
+    pub owner: String,
+    pub amount: u64,
+    pub ui_amount: String,
+    /// Share of the supply, in percent.
+    pub share: f64,
+    pub token_accounts: usize,
+}
+
+/// The holders of a mint at a slot.
+#[derive(Serialize)]
+pub struct HolderSnapshot {
+    pub mint: String,
+    pub token_program: String,
+    pub slot: Slot,
+    pub decimals: u8,
+    pub supply: u64,
+    /// Supply minus the balances of the excluded owners.
+    pub circulating: u64,
+    pub excluded: Vec<String>,
+    pub token_accounts: usize,
+    /// Owners with a non-zero balance, largest first.
+    pub holders: Vec<Holder>,
+}
+
+/// Reads every token account of `mint` and groups the balances by owner.
+///
+/// Balances of the `excluded` owners, for example a treasury, still show up
+/// as holders but do not count as circulating.
+pub fn take_snapshot(
+    rpc_client: &RpcClient,
+    mint: &MintInfo,
+    min_slot: Option<Slot>,
+    excluded: &[Pubkey],
+) -> Result<HolderSnapshot, Box<dyn Error>> {
+    let (slot, accounts) = get_token_accounts_by_mint(rpc_client, mint, min_slot)?;
+    let decimals = mint.mint.decimals;
+    let supply = mint.mint.supply;
+
+    let mut balances: HashMap<Pubkey, (u64, usize)> = HashMap::new();
+    for info in &accounts {
+        let (amount, count) = balances.entry(info.account.owner).or_default();
+        *amount += info.account.amount;
+        *count += 1;
+    }
+
+    let excluded_amount: u64 = excluded
+        .iter()
+        .filter_map(|owner| balances.get(owner))
+        .map(|(amount, _)| amount)
+        .sum();
+
+    let mut holders: Vec<Holder> = balances
+        .into_iter()
+        .filter(|(_, (amount, _))| *amount > 0)
+        .map(|(owner, (amount, token_accounts))| Holder {
+            owner: owner.to_string(),
+            amount,
+            ui_amount: format_amount(amount, decimals),
+            share: share(amount, supply),
+            token_accounts,
+        })
+        .collect();
+    holders.sort_by(|a, b| b.amount.cmp(&a.amount).then(a.owner.cmp(&b.owner)));
+
+    Ok(HolderSnapshot {
+        mint: mint.address.to_string(),
+        token_program: mint.token_program.to_string(),
+        slot,
+        decimals,
+        supply,
+        // The mint supply was read a little before the accounts, never go
+        // below zero if tokens were minted or burned in between.
+        circulating: supply.saturating_sub(excluded_amount),
+        excluded: excluded.iter().map(Pubkey::to_string).collect(),
+        token_accounts: accounts.len(),
+        holders,
+    })
+}
+
+impl HolderSnapshot {
+    /// One line per holder, the format used for airdrop lists.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("owner,amount,ui_amount,share,token_accounts\n");
+        for holder in &self.holders {
+            csv.push_str(&format!(
+                "{},{},{},{:.6},{}\n",
+                holder.owner, holder.amount, holder.ui_amount, holder.share, holder.token_accounts
+            ));
+        }
+        csv
+    }
+}
+
+fn share(amount: u64, supply: u64) -> f64 {
+    if supply == 0 {
+        return 0.0;
+    }
+    amount as f64 * 100.0 / supply as f64
+}
@@ -0,0 +1,357 @@
+use serde_json::json;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_response::{OptionalContext, RpcKeyedAccount};
+use solana_sdk::{
+    account::Account as SdkAccount, clock::Slot, instruction::Instruction, program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::instruction::transfer_checked;
+use spl_token_2022::state::{Account, Mint};
+use std::error::Error;
+use std::str::FromStr;
+
+/// A mint together with the token program that owns it.
+pub struct MintInfo {
+    pub address: Pubkey,
+    pub token_program: Pubkey,
+    pub mint: Mint,
+}
+
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == spl_token::id() || *program_id == spl_token_2022::id()
+}
+
+/// Fetches a mint owned by either the Token or the Token-2022 program.
+pub fn get_mint(rpc_client: &RpcClient, address: &Pubkey) -> Result<MintInfo, Box<dyn Error>> {
+    let account = rpc_client.get_account(address)?;
+    if !is_token_program(&account.owner) {
+        return Err(format!("{} is not owned by a token program", address).into());
+    }
+
+    let mint = StateWithExtensions::<Mint>::unpack(&account.data)?.base;
+    Ok(MintInfo {
+        address: *address,
+        token_program: account.owner,
+        mint,
+    })
+}
+
+/// Decodes a token account of either token program.  Token-2022 extensions
+/// are ignored.
+pub fn unpack_token_account(data: &[u8]) -> Result<Account, Box<dyn Error>> {
+    if data.len() == Account::LEN {
+        return Ok(Account::unpack(data)?);
+    }
+    Ok(StateWithExtensions::<Account>::unpack(data)?.base)
+}
+
+/// A token account together with the token program that owns it.
+pub struct TokenAccountInfo {
+    pub address: Pubkey,
+    pub token_program: Pubkey,
+    pub lamports: u64,
+    pub account: Account,
+}
+
+/// Fetches a token account owned by either the Token or the Token-2022
+/// program.
+pub fn get_token_account(
+    rpc_client: &RpcClient,
+    address: &Pubkey,
+) -> Result<TokenAccountInfo, Box<dyn Error>> {
+    let account = rpc_client.get_account(address)?;
+    if !is_token_program(&account.owner) {
+        return Err(format!("{} is not owned by a token program", address).into());
+    }
+
+    Ok(TokenAccountInfo {
+        address: *address,
+        token_program: account.owner,
+        lamports: account.lamports,
+        account: unpack_token_account(&account.data)?,
+    })
+}
+
+// Offsets in the token account layout, shared by both programs.
+const MINT_OFFSET: usize = 0;
+const OWNER_OFFSET: usize = 32;
+
+/// Fetches the token accounts of `token_program` matching `filters`, using
+/// `get_program_accounts`.
+pub fn get_token_accounts(
+    rpc_client: &RpcClient,
+    token_program: &Pubkey,
+    filters: Vec<RpcFilterType>,
+) -> Result<Vec<TokenAccountInfo>, Box<dyn Error>> {
+    let (_, accounts) = get_token_accounts_at(rpc_client, token_program, filters, None)?;
+    Ok(accounts)
+}
+
+/// Like [`get_token_accounts`], but also returns the slot the accounts were
+/// read at.  With `min_slot` the RPC node refuses to answer before it has
+/// reached that slot.
+pub fn get_token_accounts_at(
+    rpc_client: &RpcClient,
+    token_program: &Pubkey,
+    mut filters: Vec<RpcFilterType>,
+    min_slot: Option<Slot>,
+) -> Result<(Slot, Vec<TokenAccountInfo>), Box<dyn Error>> {
+    // Skips mints and, for Token-2022, multisig and other account kinds.
+    filters.push(RpcFilterType::TokenAccountState);
+
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(rpc_client.commitment()),
+            min_context_slot: min_slot,
+            ..RpcAccountInfoConfig::default()
+        },
+        with_context: Some(true),
+        ..RpcProgramAccountsConfig::default()
+    };
+    // `get_program_accounts_with_config` drops the context, so we make the
+    // request ourselves to learn the slot.
+    let response: OptionalContext<Vec<RpcKeyedAccount>> = rpc_client.send(
+        RpcRequest::GetProgramAccounts,
+        json!([token_program.to_string(), config]),
+    )?;
+    let (slot, keyed_accounts) = match response {
+        OptionalContext::Context(response) => (response.context.slot, response.value),
+        OptionalContext::NoContext(value) => (rpc_client.get_slot()?, value),
+    };
+
+    let accounts = keyed_accounts
+        .into_iter()
+        .filter_map(|keyed| {
+            let address = Pubkey::from_str(&keyed.pubkey).ok()?;
+            let account: SdkAccount = keyed.account.decode()?;
+            let token_account = unpack_token_account(&account.data).ok()?;
+            Some(TokenAccountInfo {
+                address,
+                token_program: *token_program,
+                lamports: account.lamports,
+                account: token_account,
+            })
+        })
+        .collect();
+    Ok((slot, accounts))
+}
+
+/// All the token accounts of `owner`, for both token programs.
+pub fn get_token_accounts_by_owner(
+    rpc_client: &RpcClient,
+    owner: &Pubkey,
+) -> Result<Vec<TokenAccountInfo>, Box<dyn Error>> {
+    let mut accounts = Vec::new();
+    for token_program in [spl_token::id(), spl_token_2022::id()] {
+        let filter =
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(OWNER_OFFSET, owner.as_ref()));
+        accounts.extend(get_token_accounts(
+            rpc_client,
+            &token_program,
+            vec![filter],
+        )?);
+    }
+    Ok(accounts)
+}
+
+/// All the token accounts of `mint` and the slot they were read at.
+///
+/// Only the program owning the mint is queried.  Accounts of the original
+/// Token program always have the same size, so they are also filtered by it.
+/// Token-2022 accounts grow with their extensions.
+pub fn get_token_accounts_by_mint(
+    rpc_client: &RpcClient,
+    mint: &MintInfo,
+    min_slot: Option<Slot>,
+) -> Result<(Slot, Vec<TokenAccountInfo>), Box<dyn Error>> {
+    let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+        MINT_OFFSET,
+        mint.address.as_ref(),
+    ))];
+    if mint.token_program == spl_token::id() {
+        filters.push(RpcFilterType::DataSize(Account::LEN as u64));
+    }
+    get_token_accounts_at(rpc_client, &mint.token_program, filters, min_slot)
+}
+
+/// Fetches several mints at once.  Accounts that are not mints are skipped.
+pub fn get_mints(
+    rpc_client: &RpcClient,
+    addresses: &[Pubkey],
+) -> Result<Vec<MintInfo>, Box<dyn Error>> {
+    let mut mints = Vec::new();
+    // `getMultipleAccounts` accepts at most 100 addresses.
+    for chunk in addresses.chunks(100) {
+        let accounts = rpc_client.get_multiple_accounts(chunk)?;
+        for (address, account) in chunk.iter().zip(accounts) {
+            let Some(account) = account.filter(|account| is_token_program(&account.owner)) else {
+                continue;
+            };
+            if let Ok(mint) = StateWithExtensions::<Mint>::unpack(&account.data) {
+                mints.push(MintInfo {
+                    address: *address,
+                    token_program: account.owner,
+                    mint: mint.base,
+                });
+            }
+        }
+    }
+    Ok(mints)
+}
+
+/// Instructions that move `amount` minor units of a mint from the associated
+/// token account of `sender` to the one of `recipient`, which is created
+/// first when `create_recipient_account` is set.
+pub fn transfer_instructions(
+    sender: &Pubkey,
+    recipient: &Pubkey,
+    mint: &MintInfo,
+    amount: u64,
+    create_recipient_account: bool,
+) -> Vec<Instruction> {
+    let token_program = &mint.token_program;
+    let source = get_associated_token_address_with_program_id(sender, &mint.address, token_program);
+    let destination =
+        get_associated_token_address_with_program_id(recipient, &mint.address, token_program);
+
+    let mut instructions = Vec::new();
+    if create_recipient_account {
+        instructions.push(create_associated_token_account_idempotent(
+            sender,
+            recipient,
+            &mint.address,
+            token_program,
+        ));
+    }
+    instructions.push(
+        transfer_checked(
+            token_program,
+            &source,
+            &mint.address,
+            &destination,
+            sender,
+            &[],
+            amount,
+            mint.mint.decimals,
+        )
+        .unwrap(),
+    );
+    instructions
+}
+
+/// Converts a decimal amount such as `"12.5"` into minor units.
+///
+/// Unlike `spl_token::ui_amount_to_amount` there is no `f64` rounding: amounts
+/// with more fractional digits than `decimals` are rejected.
+pub fn parse_ui_amount(amount: &str, decimals: u8) -> Option<u64> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if fraction.len() > decimals as usize || (whole.is_empty() && fraction.is_empty()) {
+        return None;
+    }
+    if !whole
+        .chars()
+        .chain(fraction.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let scale = 10_u64.checked_pow(decimals as u32)?;
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<u64>().ok()? * 10_u64.pow((decimals as usize - fraction.len()) as u32)
+    };
+
+    whole.checked_mul(scale)?.checked_add(fraction)
+}
+
+/// Formats minor units as a decimal amount, the inverse of [`parse_ui_amount`].
+pub fn format_amount(amount: u64, decimals: u8) -> String {
+    if decimals == 0 {
+        return amount.to_string();
+    }
+    let digits = format!("{:0width$}", amount, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    format!("{}.{}", whole, fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_amounts() {
+        assert_eq!(parse_ui_amount("12.5", 6), Some(12_500_000));
+        assert_eq!(parse_ui_amount("12", 6), Some(12_000_000));
+        assert_eq!(parse_ui_amount("12.", 6), Some(12_000_000));
+        assert_eq!(parse_ui_amount(".5", 6), Some(500_000));
+        assert_eq!(parse_ui_amount("0.000001", 6), Some(1));
+    }
+
+    #[test]
+    fn parses_amounts_without_decimals() {
+        assert_eq!(parse_ui_amount("7", 0), Some(7));
+        assert_eq!(parse_ui_amount("7.", 0), Some(7));
+        assert_eq!(parse_ui_amount("7.0", 0), None);
+        assert_eq!(format_amount(7, 0), "7");
+    }
+
+    #[test]
+    fn rejects_too_many_fractional_digits() {
+        assert_eq!(parse_ui_amount("0.0000001", 6), None);
+        assert_eq!(parse_ui_amount("1.10", 1), None);
+    }
+
+    #[test]
+    fn rejects_malformed_amounts() {
+        for amount in ["", ".", "-1", "+1", "1e3", "1.2.3", " 1", "1,5", "abc"] {
+            assert_eq!(parse_ui_amount(amount, 6), None, "{:?}", amount);
+        }
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        assert_eq!(parse_ui_amount("18446744073709551615", 0), Some(u64::MAX));
+        assert_eq!(parse_ui_amount("18446744073709551616", 0), None);
+        assert_eq!(parse_ui_amount("18446744073709.551615", 6), Some(u64::MAX));
+        assert_eq!(parse_ui_amount("18446744073709.551616", 6), None);
+        assert_eq!(parse_ui_amount("18446744073710", 6), None);
+        // 10^20 minor units per token does not fit a u64.
+        assert_eq!(parse_ui_amount("0", 20), None);
+    }
+
+    #[test]
+    fn formats_amounts() {
+        assert_eq!(format_amount(12_500_000, 6), "12.500000");
+        assert_eq!(format_amount(1, 6), "0.000001");
+        assert_eq!(format_amount(0, 9), "0.000000000");
+        assert_eq!(format_amount(u64::MAX, 6), "18446744073709.551615");
+    }
+
+    #[test]
+    fn format_round_trips() {
+        for (amount, decimals) in [(0, 0), (1, 9), (u64::MAX, 0), (u64::MAX, 19), (42, 2)] {
+            assert_eq!(
+                parse_ui_amount(&format_amount(amount, decimals), decimals),
+                Some(amount)
+            );
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::token::unpack_token_account;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction, program_option::COption, pubkey::Pubkey, system_instruction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use spl_token::instruction::{close_account, sync_native};
+use spl_token::native_mint;
+use std::error::Error;
+
+/// The wrapped SOL account of an owner, which is always its associated token
+/// account for the native mint.
+pub fn wsol_address(owner: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, &native_mint::id())
+}
+
+/// State of a wrapped SOL account.
+pub struct WsolBalance {
+    pub address: Pubkey,
+    /// Wrapped amount the token program knows about.
+    pub amount: u64,
+    /// Lamports held on top of the rent reserve that are not yet part of
+    /// `amount`, until `sync_native` is called.
+    pub unsynced: u64,
+    pub lamports: u64,
+}
+
+/// Reads the wrapped SOL account of `owner`, `None` if it does not exist.
+pub fn get_wsol_balance(
+    rpc_client: &RpcClient,
+    owner: &Pubkey,
+) -> Result<Option<WsolBalance>, Box<dyn Error>> {
+    let address = wsol_address(owner);
+    let Some(account) = rpc_client
+        .get_account_with_commitment(&address, rpc_client.commitment())?
+        .value
+    else {
+        return Ok(None);
+    };
+
+    let token_account = unpack_token_account(&account.data)?;
+    let COption::Some(rent_reserve) = token_account.is_native else {
+        return Err(format!("{} is not a wrapped SOL account", address).into());
+    };
+    Ok(Some(WsolBalance {
+        address,
+        amount: token_account.amount,
+        unsynced: account
+            .lamports
+            .saturating_sub(rent_reserve)
+            .saturating_sub(token_account.amount),
+        lamports: account.lamports,
+    }))
+}
+
+/// Instructions that move `lamports` into the wrapped SOL account of `owner`,
+/// creating it when needed.
+///
+/// `sync_native` always runs last, so lamports sent to the account earlier
+/// are wrapped as well and running this again never fails.
+pub fn wrap_instructions(owner: &Pubkey, lamports: u64) -> Vec<Instruction> {
+    let address = wsol_address(owner);
+    let mut instructions = vec![create_associated_token_account_idempotent(
+        owner,
+        owner,
+        &native_mint::id(),
+        &spl_token::id(),
+    )];
+    if lamports > 0 {
+        instructions.push(system_instruction::transfer(owner, &address, lamports));
+    }
+    instructions.push(sync_native(&spl_token::id(), &address).unwrap());
+    instructions
+}
+
+/// Closes the wrapped SOL account of `owner`, all its lamports, rent
+/// included, go back to `owner` as SOL.
+pub fn unwrap_instruction(owner: &Pubkey) -> Instruction {
+    close_account(&spl_token::id(), &wsol_address(owner), owner, owner, &[]).unwrap()
+}
@@ -3,3 +3,14 @@ use anchor_lang::prelude::*;
 #[constant]
 pub const SEED: &str = "anchor";
 pub const ANCHOR_DISCRIMINATOR: usize = 8;
+
+/// Paid out of the offer rent to whoever refunds an expired offer.
+#[constant]
+pub const KEEPER_REWARD_LAMPORTS: u64 = 100_000;
+
+#[constant]
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Fees are in basis points, hundredths of a percent.
+#[constant]
+pub const BPS_DENOMINATOR: u16 = 10_000;
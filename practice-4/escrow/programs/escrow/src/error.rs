@@ -2,6 +2,30 @@ use anchor_lang::prelude::*;
 
 #[error_code]
 pub enum ErrorCode {
-    #[msg("Custom error message")]
-    CustomError,
+    #[msg("Amount must be more than zero")]
+    ZeroAmount,
+    #[msg("Offered and wanted mints must differ")]
+    IdenticalMints,
+    #[msg("Fill amount is more than what remains of the offer")]
+    InvalidFillAmount,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Offer has expired")]
+    OfferExpired,
+    #[msg("Offer has not expired yet")]
+    OfferNotExpired,
+    #[msg("Taker is not allowed to take this offer")]
+    WrongTaker,
+    #[msg("Vault does not hold the amount the offer expects")]
+    VaultAmountMismatch,
+    #[msg("Signer is not allowed to change the config")]
+    Unauthorized,
+    #[msg("Fee must be at most 10000 basis points")]
+    InvalidFee,
+    #[msg("Offer no longer matches the amounts the taker expects")]
+    SlippageExceeded,
+    #[msg("Token B accounts do not match what the offer wants")]
+    WrongTokenB,
 }
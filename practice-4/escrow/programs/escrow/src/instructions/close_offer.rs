@@ -3,12 +3,12 @@ use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{
-        CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked, close_account,
-        transfer_checked,
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
     },
 };
 
-use crate::Offer;
+use crate::{error::ErrorCode, Offer};
 
 #[derive(Accounts)]
 pub struct CloseOffer<'info> {
@@ -18,8 +18,10 @@ pub struct CloseOffer<'info> {
     #[account(
         mut,
         close = maker,
-
+        has_one = maker,
         has_one = token_mint_a,
+        seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump
     )]
     offer: Account<'info, Offer>,
 
@@ -47,6 +49,12 @@ pub struct CloseOffer<'info> {
 }
 
 pub fn close(context: Context<CloseOffer>) -> Result<()> {
+    // Tokens sent to the vault by others are returned to the maker as well.
+    require!(
+        context.accounts.vault.amount >= context.accounts.offer.token_a_remaining_amount,
+        ErrorCode::VaultAmountMismatch
+    );
+
     let transfer_accounts = TransferChecked {
         from: context.accounts.vault.to_account_info(),
         mint: context.accounts.token_mint_a.to_account_info(),
@@ -69,11 +77,11 @@ pub fn close(context: Context<CloseOffer>) -> Result<()> {
 
     transfer_checked(
         cpi_context,
-        context.accounts.offer.token_a_offered_amount,
+        context.accounts.vault.amount,
         context.accounts.token_mint_a.decimals,
-    );
+    )?;
 
-    let closeVault = CloseAccount {
+    let close_vault = CloseAccount {
         account: context.accounts.vault.to_account_info(),
         destination: context.accounts.maker.to_account_info(),
         authority: context.accounts.offer.to_account_info(),
@@ -81,11 +89,9 @@ pub fn close(context: Context<CloseOffer>) -> Result<()> {
 
     let cpi_context_vault = CpiContext::new_with_signer(
         context.accounts.token_program.to_account_info(),
-        closeVault,
+        close_vault,
         &signer_seeds,
     );
 
-    close_account(cpi_context_vault);
-
-    Ok(())
+    close_account(cpi_context_vault)
 }
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+use crate::{error::ErrorCode, program::Escrow, Config, ANCHOR_DISCRIMINATOR, CONFIG_SEED};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    // Only the upgrade authority of the program may create the config, so it
+    // can't be taken over by whoever calls first after a deploy.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = ANCHOR_DISCRIMINATOR + Config::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Escrow>,
+
+    #[account(
+        seeds = [program.key().as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize(
+    context: Context<InitializeConfig>,
+    fee_bps: u16,
+    treasury: Pubkey,
+) -> Result<()> {
+    Config::validate_fee(fee_bps)?;
+    context.accounts.config.set_inner(Config {
+        admin: context.accounts.admin.key(),
+        fee_bps,
+        treasury,
+        bump: context.bumps.config,
+    });
+    Ok(())
+}
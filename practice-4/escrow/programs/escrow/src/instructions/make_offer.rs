@@ -2,13 +2,10 @@ use anchor_lang::prelude::*;
 
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{
-        CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked, close_account,
-        transfer_checked,
-    },
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::{ANCHOR_DISCRIMINATOR, Offer};
+use crate::{error::ErrorCode, Offer, ANCHOR_DISCRIMINATOR};
 
 #[derive(Accounts)]
 #[instruction(id: u64)]
@@ -19,8 +16,9 @@ pub struct MakeOffer<'info> {
     #[account(mint::token_program = token_program)]
     pub token_mint_a: InterfaceAccount<'info, Mint>,
 
-    #[account(mint::token_program = token_program)]
-    pub token_mint_b: InterfaceAccount<'info, Mint>,
+    // Left out when the maker wants lamports.
+    #[account(mint::token_program = token_program_b)]
+    pub token_mint_b: Option<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
@@ -49,12 +47,40 @@ pub struct MakeOffer<'info> {
 
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
+    // Owner of `token_mint_b`, which need not be the one of `token_mint_a`.
+    pub token_program_b: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn send_offered_tokens_to_vault(
+pub fn validate_offer(
     context: &Context<MakeOffer>,
     token_a_offered_amount: u64,
+    token_b_wanted_amount: u64,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    require!(
+        token_a_offered_amount > 0 && token_b_wanted_amount > 0,
+        ErrorCode::ZeroAmount
+    );
+    if let Some(token_mint_b) = &context.accounts.token_mint_b {
+        require_keys_neq!(
+            context.accounts.token_mint_a.key(),
+            token_mint_b.key(),
+            ErrorCode::IdenticalMints
+        );
+    }
+    if let Some(expires_at) = expires_at {
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            ErrorCode::InvalidExpiry
+        );
+    }
+    Ok(())
+}
+
+pub fn send_offered_tokens_to_vault(
+    context: &mut Context<MakeOffer>,
+    token_a_offered_amount: u64,
 ) -> Result<()> {
     let transfer_accounts = TransferChecked {
         from: context.accounts.maker_token_account_a.to_account_info(),
@@ -72,7 +98,16 @@ pub fn send_offered_tokens_to_vault(
         cpi_context,
         token_a_offered_amount,
         context.accounts.token_mint_a.decimals,
-    )
+    )?;
+
+    // A mint with a transfer fee would leave less in the vault than offered.
+    context.accounts.vault.reload()?;
+    require_eq!(
+        context.accounts.vault.amount,
+        token_a_offered_amount,
+        ErrorCode::VaultAmountMismatch
+    );
+    Ok(())
 }
 
 pub fn save_offer(
@@ -80,14 +115,24 @@ pub fn save_offer(
     id: u64,
     token_a_offered_amount: u64,
     token_b_wanted_amount: u64,
+    expires_at: Option<i64>,
+    allowed_taker: Option<Pubkey>,
 ) -> Result<()> {
     context.accounts.offer.set_inner(Offer {
         id,
         maker: context.accounts.maker.key(),
         token_mint_a: context.accounts.token_mint_a.key(),
-        token_mint_b: context.accounts.token_mint_b.key(),
+        token_mint_b: context
+            .accounts
+            .token_mint_b
+            .as_ref()
+            .map_or(Pubkey::default(), |token_mint_b| token_mint_b.key()),
         token_a_offered_amount,
         token_b_wanted_amount,
+        token_a_remaining_amount: token_a_offered_amount,
+        token_b_remaining_amount: token_b_wanted_amount,
+        allowed_taker,
+        expires_at,
         bump: context.bumps.offer,
     });
     Ok(())
@@ -4,3 +4,11 @@ pub mod take_offer;
 pub use take_offer::*;
 pub mod close_offer;
 pub use close_offer::*;
+pub mod refund_expired_offer;
+pub use refund_expired_offer::*;
+pub mod initialize_config;
+pub use initialize_config::*;
+pub mod update_config;
+pub use update_config::*;
+pub mod update_offer;
+pub use update_offer::*;
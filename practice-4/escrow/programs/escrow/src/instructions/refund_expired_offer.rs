@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{error::ErrorCode, Offer, KEEPER_REWARD_LAMPORTS};
+
+#[derive(Accounts)]
+pub struct RefundExpiredOffer<'info> {
+    // Anyone may refund an expired offer, and is paid for it.
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = token_mint_a,
+        seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump
+    )]
+    offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    // The maker may have closed their account since making the offer.
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn refund(context: Context<RefundExpiredOffer>) -> Result<()> {
+    require!(
+        context
+            .accounts
+            .offer
+            .is_expired(Clock::get()?.unix_timestamp),
+        ErrorCode::OfferNotExpired
+    );
+
+    let signer_seeds: [&[&[u8]]; 1] = [&[
+        b"offer",
+        context.accounts.maker.to_account_info().key.as_ref(),
+        &context.accounts.offer.id.to_le_bytes()[..],
+        &[context.accounts.offer.bump],
+    ]];
+
+    let transfer_accounts = TransferChecked {
+        from: context.accounts.vault.to_account_info(),
+        mint: context.accounts.token_mint_a.to_account_info(),
+        to: context.accounts.maker_token_account_a.to_account_info(),
+        authority: context.accounts.offer.to_account_info(),
+    };
+    let cpi_context = CpiContext::new_with_signer(
+        context.accounts.token_program.to_account_info(),
+        transfer_accounts,
+        &signer_seeds,
+    );
+    transfer_checked(
+        cpi_context,
+        context.accounts.vault.amount,
+        context.accounts.token_mint_a.decimals,
+    )?;
+
+    let close_accounts = CloseAccount {
+        account: context.accounts.vault.to_account_info(),
+        destination: context.accounts.maker.to_account_info(),
+        authority: context.accounts.offer.to_account_info(),
+    };
+    let cpi_context = CpiContext::new_with_signer(
+        context.accounts.token_program.to_account_info(),
+        close_accounts,
+        &signer_seeds,
+    );
+    close_account(cpi_context)?;
+
+    // The reward comes out of the offer rent, the rest goes back to the maker.
+    context
+        .accounts
+        .offer
+        .sub_lamports(KEEPER_REWARD_LAMPORTS)?;
+    context
+        .accounts
+        .keeper
+        .add_lamports(KEEPER_REWARD_LAMPORTS)?;
+    context
+        .accounts
+        .offer
+        .close(context.accounts.maker.to_account_info())
+}
@@ -1,14 +1,17 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
 
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{
-        CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked, close_account,
-        transfer_checked,
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
     },
 };
 
-use crate::Offer;
+use crate::{error::ErrorCode, Config, Offer, CONFIG_SEED};
 
 #[derive(Accounts)]
 pub struct TakeOffer<'info> {
@@ -20,7 +23,8 @@ pub struct TakeOffer<'info> {
 
     pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
 
-    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+    // The token B accounts are left out when the offer wants lamports.
+    pub token_mint_b: Option<Box<InterfaceAccount<'info, Mint>>>,
     #[account(
         init_if_needed,
         payer = taker,
@@ -34,26 +38,25 @@ pub struct TakeOffer<'info> {
         mut,
         associated_token::mint = token_mint_b,
         associated_token::authority = taker,
-        associated_token::token_program = token_program,
+        associated_token::token_program = token_program_b,
     )]
-    pub taker_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub taker_token_account_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     #[account(
         init_if_needed,
         payer = taker,
         associated_token::mint = token_mint_b,
         associated_token::authority = maker,
-        associated_token::token_program = token_program,
+        associated_token::token_program = token_program_b,
     )]
-    pub maker_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub maker_token_account_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
+    // Closed in `withdraw_and_close_vault` once the last token A is taken.
     #[account(
         mut,
-        close = maker,
         has_one = maker,
         has_one = token_mint_a,
-        has_one = token_mint_b,
-        // seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
-        // bump = offer.bump
+        seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump
     )]
     offer: Account<'info, Offer>,
     #[account(
@@ -64,31 +67,165 @@ pub struct TakeOffer<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(
+        has_one = treasury,
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(mut)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_b,
+    )]
+    pub treasury_token_account_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
+    // Owner of `token_mint_b`, which need not be the one of `token_mint_a`.
+    pub token_program_b: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn send_wanted_tokens_to_maker(ctx: &Context<TakeOffer>) -> Result<()> {
+/// Checks the offer can still be taken and the fill, and returns how much
+/// token B the taker pays for it.
+pub fn token_b_amount_for_fill(
+    ctx: &Context<TakeOffer>,
+    token_a_amount: u64,
+    max_token_b_to_pay: u64,
+) -> Result<u64> {
+    let offer = &ctx.accounts.offer;
+    require_keys_neq!(ctx.accounts.taker.key(), offer.maker, ErrorCode::WrongTaker);
+    if let Some(allowed_taker) = offer.allowed_taker {
+        require_keys_eq!(
+            ctx.accounts.taker.key(),
+            allowed_taker,
+            ErrorCode::WrongTaker
+        );
+    }
+    require!(
+        !offer.is_expired(Clock::get()?.unix_timestamp),
+        ErrorCode::OfferExpired
+    );
+    let token_mint_b = ctx
+        .accounts
+        .token_mint_b
+        .as_ref()
+        .map_or(Pubkey::default(), |token_mint_b| token_mint_b.key());
+    require_keys_eq!(token_mint_b, offer.token_mint_b, ErrorCode::WrongTokenB);
+    require!(token_a_amount > 0, ErrorCode::ZeroAmount);
+    require!(
+        token_a_amount <= offer.token_a_remaining_amount,
+        ErrorCode::InvalidFillAmount
+    );
+    // Anyone can send tokens to the vault, so it may hold more than remains
+    // but never less.
+    require!(
+        ctx.accounts.vault.amount >= offer.token_a_remaining_amount,
+        ErrorCode::VaultAmountMismatch
+    );
+    let token_b_amount = offer
+        .token_b_amount_for(token_a_amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    // The maker may have repriced the offer since the taker looked at it.
+    require!(
+        token_b_amount <= max_token_b_to_pay,
+        ErrorCode::SlippageExceeded
+    );
+    Ok(token_b_amount)
+}
+
+/// Pays `token_b_amount` for the fill, in lamports or token B as the offer
+/// wants.  The protocol fee goes to the treasury and the rest to the maker.
+pub fn send_wanted_tokens_to_maker<'info>(
+    ctx: &Context<'_, '_, '_, '_, TakeOffer<'info>>,
+    token_b_amount: u64,
+) -> Result<()> {
+    let fee = ctx
+        .accounts
+        .config
+        .fee_for(token_b_amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let maker_amount = token_b_amount
+        .checked_sub(fee)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    pay(
+        ctx,
+        ctx.accounts.maker.to_account_info(),
+        &ctx.accounts.maker_token_account_b,
+        maker_amount,
+    )?;
+    if fee == 0 {
+        return Ok(());
+    }
+    pay(
+        ctx,
+        ctx.accounts.treasury.to_account_info(),
+        &ctx.accounts.treasury_token_account_b,
+        fee,
+    )
+}
+
+/// Sends `amount` of what the offer wants from the taker to `recipient`,
+/// lamports to the account itself and tokens to `recipient_token_account`.
+fn pay<'info>(
+    ctx: &Context<'_, '_, '_, '_, TakeOffer<'info>>,
+    recipient: AccountInfo<'info>,
+    recipient_token_account: &Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    amount: u64,
+) -> Result<()> {
+    // An empty treasury can't be sent a fee of less lamports than it needs to
+    // be rent exempt, it has to be funded first.
+    if ctx.accounts.offer.wants_sol() {
+        let transfer_accounts = Transfer {
+            from: ctx.accounts.taker.to_account_info(),
+            to: recipient,
+        };
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            transfer_accounts,
+        );
+        return transfer(cpi_context, amount);
+    }
+
+    let (
+        Some(token_mint_b),
+        Some(taker_token_account_b),
+        Some(recipient_token_account),
+        Some(token_program_b),
+    ) = (
+        &ctx.accounts.token_mint_b,
+        &ctx.accounts.taker_token_account_b,
+        recipient_token_account,
+        &ctx.accounts.token_program_b,
+    )
+    else {
+        return err!(ErrorCode::WrongTokenB);
+    };
     let transfer_accounts = TransferChecked {
-        from: ctx.accounts.taker_token_account_b.to_account_info(),
-        mint: ctx.accounts.token_mint_b.to_account_info(),
-        to: ctx.accounts.maker_token_account_b.to_account_info(),
+        from: taker_token_account_b.to_account_info(),
+        mint: token_mint_b.to_account_info(),
+        to: recipient_token_account.to_account_info(),
         authority: ctx.accounts.taker.to_account_info(),
     };
 
-    let cpi_context = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        transfer_accounts,
-    );
-    transfer_checked(
-        cpi_context,
-        ctx.accounts.offer.token_b_wanted_amount,
-        ctx.accounts.token_mint_b.decimals,
-    )
+    let cpi_context = CpiContext::new(token_program_b.to_account_info(), transfer_accounts);
+    transfer_checked(cpi_context, amount, token_mint_b.decimals)
 }
 
-pub fn withdraw_and_close_vault(ctx: Context<TakeOffer>) -> Result<()> {
+pub fn withdraw_and_close_vault(
+    ctx: Context<TakeOffer>,
+    token_a_amount: u64,
+    token_b_amount: u64,
+    min_token_a_to_receive: u64,
+) -> Result<()> {
     let signer_seeds: [&[&[u8]]; 1] = [&[
         b"offer",
         ctx.accounts.maker.to_account_info().key.as_ref(),
@@ -96,6 +233,21 @@ pub fn withdraw_and_close_vault(ctx: Context<TakeOffer>) -> Result<()> {
         &[ctx.accounts.offer.bump],
     ]];
 
+    let token_a_remaining_amount = ctx
+        .accounts
+        .offer
+        .token_a_remaining_amount
+        .checked_sub(token_a_amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    // The last fill empties the vault so it can be closed, tokens sent to it
+    // by others go to the taker.
+    let withdraw_amount = if token_a_remaining_amount == 0 {
+        ctx.accounts.vault.amount
+    } else {
+        token_a_amount
+    };
+
+    let taker_balance_before = ctx.accounts.taker_token_account_a.amount;
     let accounts = TransferChecked {
         from: ctx.accounts.vault.to_account_info(),
         mint: ctx.accounts.token_mint_a.to_account_info(),
@@ -110,9 +262,34 @@ pub fn withdraw_and_close_vault(ctx: Context<TakeOffer>) -> Result<()> {
     );
     transfer_checked(
         cpi_context,
-        ctx.accounts.vault.amount,
+        withdraw_amount,
         ctx.accounts.token_mint_a.decimals,
     )?;
+
+    // What arrives can be less than was sent when the mint takes a transfer
+    // fee, which its authority may raise at any time.
+    ctx.accounts.taker_token_account_a.reload()?;
+    let received = ctx
+        .accounts
+        .taker_token_account_a
+        .amount
+        .checked_sub(taker_balance_before)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    require!(
+        received >= min_token_a_to_receive,
+        ErrorCode::SlippageExceeded
+    );
+
+    let offer = &mut ctx.accounts.offer;
+    offer.token_a_remaining_amount = token_a_remaining_amount;
+    offer.token_b_remaining_amount = offer
+        .token_b_remaining_amount
+        .checked_sub(token_b_amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    if token_a_remaining_amount > 0 {
+        return Ok(());
+    }
+
     let accounts = CloseAccount {
         account: ctx.accounts.vault.to_account_info(),
         destination: ctx.accounts.taker.to_account_info(),
@@ -125,5 +302,9 @@ pub fn withdraw_and_close_vault(ctx: Context<TakeOffer>) -> Result<()> {
         &signer_seeds,
     );
 
-    close_account(cpi_context)
+    close_account(cpi_context)?;
+
+    ctx.accounts
+        .offer
+        .close(ctx.accounts.maker.to_account_info())
 }
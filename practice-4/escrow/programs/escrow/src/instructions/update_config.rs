@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, Config, CONFIG_SEED};
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ ErrorCode::Unauthorized,
+        seeds = [CONFIG_SEED],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn update(
+    context: Context<UpdateConfig>,
+    admin: Pubkey,
+    fee_bps: u16,
+    treasury: Pubkey,
+) -> Result<()> {
+    Config::validate_fee(fee_bps)?;
+    let config = &mut context.accounts.config;
+    config.admin = admin;
+    config.fee_bps = fee_bps;
+    config.treasury = treasury;
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{error::ErrorCode, Offer};
+
+#[derive(Accounts)]
+pub struct UpdateOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = maker,
+        has_one = token_mint_a,
+        seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump
+    )]
+    offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn validate_update(
+    context: &Context<UpdateOffer>,
+    token_a_amount: u64,
+    token_b_wanted_amount: u64,
+) -> Result<()> {
+    require!(
+        token_a_amount > 0 && token_b_wanted_amount > 0,
+        ErrorCode::ZeroAmount
+    );
+    require!(
+        context.accounts.vault.amount >= context.accounts.offer.token_a_remaining_amount,
+        ErrorCode::VaultAmountMismatch
+    );
+    Ok(())
+}
+
+/// Deposits or withdraws token A so the vault holds what the offer is left
+/// with, `token_a_amount`.
+pub fn resize_vault(context: &mut Context<UpdateOffer>, token_a_amount: u64) -> Result<()> {
+    let remaining = context.accounts.offer.token_a_remaining_amount;
+    if token_a_amount > remaining {
+        let transfer_accounts = TransferChecked {
+            from: context.accounts.maker_token_account_a.to_account_info(),
+            mint: context.accounts.token_mint_a.to_account_info(),
+            to: context.accounts.vault.to_account_info(),
+            authority: context.accounts.maker.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(
+            context.accounts.token_program.to_account_info(),
+            transfer_accounts,
+        );
+        transfer_checked(
+            cpi_context,
+            token_a_amount - remaining,
+            context.accounts.token_mint_a.decimals,
+        )?;
+    } else if token_a_amount < remaining {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"offer",
+            context.accounts.maker.to_account_info().key.as_ref(),
+            &context.accounts.offer.id.to_le_bytes()[..],
+            &[context.accounts.offer.bump],
+        ]];
+        let transfer_accounts = TransferChecked {
+            from: context.accounts.vault.to_account_info(),
+            mint: context.accounts.token_mint_a.to_account_info(),
+            to: context.accounts.maker_token_account_a.to_account_info(),
+            authority: context.accounts.offer.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(
+            context.accounts.token_program.to_account_info(),
+            transfer_accounts,
+            &signer_seeds,
+        );
+        transfer_checked(
+            cpi_context,
+            remaining - token_a_amount,
+            context.accounts.token_mint_a.decimals,
+        )?;
+    }
+
+    // A mint with a transfer fee would leave less in the vault than deposited.
+    context.accounts.vault.reload()?;
+    require!(
+        context.accounts.vault.amount >= token_a_amount,
+        ErrorCode::VaultAmountMismatch
+    );
+    Ok(())
+}
+
+/// The offer is priced afresh from what remains, earlier fills no longer
+/// count towards it.
+pub fn save_update(
+    context: Context<UpdateOffer>,
+    token_a_amount: u64,
+    token_b_wanted_amount: u64,
+) -> Result<()> {
+    let offer = &mut context.accounts.offer;
+    offer.token_a_offered_amount = token_a_amount;
+    offer.token_b_wanted_amount = token_b_wanted_amount;
+    offer.token_a_remaining_amount = token_a_amount;
+    offer.token_b_remaining_amount = token_b_wanted_amount;
+    Ok(())
+}
@@ -15,27 +15,105 @@ declare_id!("5MaJShYoCFPtoghs5pTyVHa44w4BBvxCTJsZCjnCe3y1");
 pub mod escrow {
     use super::*;
 
+    /// Makes an offer, which can be taken until `expires_at` when it is set,
+    /// and only by `allowed_taker` when that is set.  Without `token_mint_b`
+    /// the maker wants `token_b_wanted_amount` lamports.
     pub fn make_offer(
-        context: Context<MakeOffer>,
+        mut context: Context<MakeOffer>,
         id: u64,
         token_a_offered_amount: u64,
         token_b_wanted_amount: u64,
+        expires_at: Option<i64>,
+        allowed_taker: Option<Pubkey>,
     ) -> Result<()> {
-        instructions::make_offer::send_offered_tokens_to_vault(&context, token_a_offered_amount)?;
+        instructions::make_offer::validate_offer(
+            &context,
+            token_a_offered_amount,
+            token_b_wanted_amount,
+            expires_at,
+        )?;
+        instructions::make_offer::send_offered_tokens_to_vault(
+            &mut context,
+            token_a_offered_amount,
+        )?;
         instructions::make_offer::save_offer(
             context,
             id,
             token_a_offered_amount,
             token_b_wanted_amount,
+            expires_at,
+            allowed_taker,
         )
     }
 
-    pub fn take_offer(context: Context<TakeOffer>) -> Result<()> {
-        instructions::take_offer::send_wanted_tokens_to_maker(&context)?;
-        instructions::take_offer::withdraw_and_close_vault(context)
+    /// Creates the protocol config, only the upgrade authority of the program
+    /// may do so.
+    pub fn initialize_config(
+        context: Context<InitializeConfig>,
+        fee_bps: u16,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        instructions::initialize_config::initialize(context, fee_bps, treasury)
+    }
+
+    pub fn update_config(
+        context: Context<UpdateConfig>,
+        admin: Pubkey,
+        fee_bps: u16,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        instructions::update_config::update(context, admin, fee_bps, treasury)
+    }
+
+    /// Takes `token_a_amount` of the offered tokens, the whole offer or a part
+    /// of it, and pays for them pro rata in token B or lamports.  The protocol
+    /// fee is taken out of what the maker receives.
+    ///
+    /// Fails when the fill would cost more than `max_token_b_to_pay` or the
+    /// taker would receive less than `min_token_a_to_receive`.
+    pub fn take_offer(
+        context: Context<TakeOffer>,
+        token_a_amount: u64,
+        max_token_b_to_pay: u64,
+        min_token_a_to_receive: u64,
+    ) -> Result<()> {
+        let token_b_amount = instructions::take_offer::token_b_amount_for_fill(
+            &context,
+            token_a_amount,
+            max_token_b_to_pay,
+        )?;
+        instructions::take_offer::send_wanted_tokens_to_maker(&context, token_b_amount)?;
+        instructions::take_offer::withdraw_and_close_vault(
+            context,
+            token_a_amount,
+            token_b_amount,
+            min_token_a_to_receive,
+        )
+    }
+
+    /// Changes what remains of an offer to `token_a_amount` for
+    /// `token_b_wanted_amount`, depositing or withdrawing token A as needed.
+    pub fn update_offer(
+        mut context: Context<UpdateOffer>,
+        token_a_amount: u64,
+        token_b_wanted_amount: u64,
+    ) -> Result<()> {
+        instructions::update_offer::validate_update(
+            &context,
+            token_a_amount,
+            token_b_wanted_amount,
+        )?;
+        instructions::update_offer::resize_vault(&mut context, token_a_amount)?;
+        instructions::update_offer::save_update(context, token_a_amount, token_b_wanted_amount)
     }
 
     pub fn close_offer(context: Context<CloseOffer>) -> Result<()> {
         instructions::close_offer::close(context)
     }
+
+    /// Returns the tokens and rent of an expired offer to the maker, the
+    /// signer gets `KEEPER_REWARD_LAMPORTS` for it.
+    pub fn refund_expired_offer(context: Context<RefundExpiredOffer>) -> Result<()> {
+        instructions::refund_expired_offer::refund(context)
+    }
 }
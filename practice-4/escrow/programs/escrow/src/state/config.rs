@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, BPS_DENOMINATOR};
+
+/// Protocol settings, a single account at the `config` seed.
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    // Share of the token B paid by takers that goes to the treasury.
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub bump: u8,
+}
+
+impl Config {
+    pub fn validate_fee(fee_bps: u16) -> Result<()> {
+        require!(fee_bps <= BPS_DENOMINATOR, ErrorCode::InvalidFee);
+        Ok(())
+    }
+
+    /// The treasury's share of `token_b_amount`, rounded down.
+    pub fn fee_for(&self, token_b_amount: u64) -> Option<u64> {
+        let fee =
+            (token_b_amount as u128).checked_mul(self.fee_bps as u128)? / BPS_DENOMINATOR as u128;
+        u64::try_from(fee).ok()
+    }
+}
@@ -1,2 +1,4 @@
 pub mod offer;
 pub use offer::*;
+pub mod config;
+pub use config::*;
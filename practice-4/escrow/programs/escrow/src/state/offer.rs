@@ -6,8 +6,46 @@ pub struct Offer {
     pub id: u64,
     pub maker: Pubkey,
     pub token_mint_a: Pubkey,
+    // `Pubkey::default()` when the maker wants lamports rather than a token.
     pub token_mint_b: Pubkey,
     pub token_a_offered_amount: u64,
     pub token_b_wanted_amount: u64,
+    // What is left after partial fills, the two amounts above keep the price.
+    pub token_a_remaining_amount: u64,
+    pub token_b_remaining_amount: u64,
+    // Only this taker may take the offer when set.  Kept before `expires_at`
+    // so it sits at a fixed offset, clients filter offers by it.
+    pub allowed_taker: Option<Pubkey>,
+    // Unix timestamp after which the offer can no longer be taken, and anyone
+    // can refund it to the maker.
+    pub expires_at: Option<i64>,
     pub bump: u8,
 }
+
+impl Offer {
+    pub fn wants_sol(&self) -> bool {
+        self.token_mint_b == Pubkey::default()
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// How much token B a taker pays for `token_a_amount` of the remaining
+    /// token A.
+    ///
+    /// The price is pro rata to the original amounts, rounded up so the maker
+    /// never receives less than their price.  The fill that empties the offer
+    /// pays exactly what remains, so the maker receives
+    /// `token_b_wanted_amount` in total however the offer is split.
+    pub fn token_b_amount_for(&self, token_a_amount: u64) -> Option<u64> {
+        if token_a_amount == self.token_a_remaining_amount {
+            return Some(self.token_b_remaining_amount);
+        }
+
+        let numerator = (token_a_amount as u128).checked_mul(self.token_b_wanted_amount as u128)?;
+        let token_b_amount = numerator.div_ceil(self.token_a_offered_amount as u128);
+        let token_b_amount = u64::try_from(token_b_amount).ok()?;
+        Some(token_b_amount.min(self.token_b_remaining_amount))
+    }
+}
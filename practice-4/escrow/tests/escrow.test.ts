@@ -12,14 +12,17 @@ import {
   TransactionInstruction,
 } from "@solana/web3.js";
 import {
+  ExtensionType,
   MINT_SIZE,
   TOKEN_2022_PROGRAM_ID,
-  type TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
   createAssociatedTokenAccountIdempotentInstruction,
   createInitializeMint2Instruction,
+  createInitializeTransferFeeConfigInstruction,
   createMintToInstruction,
   getAssociatedTokenAddressSync,
   getMinimumBalanceForRentExemptMint,
+  getMintLen,
 } from "@solana/spl-token";
 import { randomBytes } from "crypto";
 
@@ -28,6 +31,8 @@ import { confirmTransaction, makeKeypairs } from "@solana-developers/helpers";
 const TOKEN_PROGRAM: typeof TOKEN_2022_PROGRAM_ID | typeof TOKEN_PROGRAM_ID =
   TOKEN_2022_PROGRAM_ID;
 
+const U64_MAX = new BN("18446744073709551615");
+
 export const getRandomBigNumber = (size: number = 8) => {
   return new BN(randomBytes(size));
 };
@@ -52,7 +57,8 @@ const createTokenAndMintTo = async (
   tokenMint: PublicKey,
   decimals: number,
   mintAuthority: PublicKey,
-  mintTo: Array<{ recepient: PublicKey; amount: number }>
+  mintTo: Array<{ recepient: PublicKey; amount: number }>,
+  tokenProgram: PublicKey = TOKEN_PROGRAM
 ): Promise<Array<TransactionInstruction>> => {
   let minimumLamports = await getMinimumBalanceForRentExemptMint(connection);
 
@@ -62,14 +68,14 @@ const createTokenAndMintTo = async (
       newAccountPubkey: tokenMint,
       lamports: minimumLamports,
       space: MINT_SIZE,
-      programId: TOKEN_PROGRAM,
+      programId: tokenProgram,
     }),
     createInitializeMint2Instruction(
       tokenMint,
       decimals,
       mintAuthority,
       null,
-      TOKEN_PROGRAM
+      tokenProgram
     ),
   ];
 
@@ -78,7 +84,7 @@ const createTokenAndMintTo = async (
       tokenMint,
       recepient,
       false,
-      TOKEN_PROGRAM
+      tokenProgram
     );
 
     return [
@@ -87,7 +93,7 @@ const createTokenAndMintTo = async (
         ataAddress,
         recepient,
         tokenMint,
-        TOKEN_PROGRAM
+        tokenProgram
       ),
       createMintToInstruction(
         tokenMint,
@@ -95,7 +101,7 @@ const createTokenAndMintTo = async (
         mintAuthority,
         amount,
         [],
-        TOKEN_PROGRAM
+        tokenProgram
       ),
     ];
   });
@@ -129,7 +135,7 @@ describe("escrow", () => {
 
   const program = anchor.workspace.Escrow as Program<Escrow>;
 
-  const [alice, bob, usdcMint, wifMint] = makeKeypairs(4);
+  const [alice, bob, usdcMint, wifMint, treasury] = makeKeypairs(5);
 
   const [aliceUsdcAccount, aliceWifAccount, bobUsdcAccount, bobWifAccount] = [
     alice,
@@ -202,25 +208,49 @@ describe("escrow", () => {
       usdcMint,
       wifMint,
     ]);
+
+    // Takes need the protocol config, which starts without a fee.  The
+    // provider wallet deployed the program, so it may create the config.
+    await program.methods
+      .initializeConfig(0, treasury.publicKey)
+      .accounts({ admin: provider.publicKey })
+      .rpc();
   });
 
   // afterAll(() => {
   //   global.console = jestConsole;
   // });
 
+  // Mints A and B may belong to different token programs.
+  const getTokenProgram = async (tokenMint: PublicKey): Promise<PublicKey> =>
+    (await connection.getAccountInfo(tokenMint))!.owner;
+
   const makeOfferTx = async (
     maker: Keypair,
     offerId: BN,
     offeredTokenMint: PublicKey,
     offeredAmount: BN,
-    wantedTokenMint: PublicKey,
-    wantedAmount: BN
+    // `null` when the maker wants lamports.
+    wantedTokenMint: PublicKey | null,
+    wantedAmount: BN,
+    expiresAt: BN | null = null,
+    allowedTaker: PublicKey | null = null
   ): Promise<{
     offerAddress: PublicKey;
     vaultAddress: PublicKey;
   }> => {
+    const tokenProgramA = await getTokenProgram(offeredTokenMint);
+    const tokenProgramB =
+      wantedTokenMint && (await getTokenProgram(wantedTokenMint));
+
     const transactionSignature = await program.methods
-      .makeOffer(offerId, offeredAmount, wantedAmount)
+      .makeOffer(
+        offerId,
+        offeredAmount,
+        wantedAmount,
+        expiresAt,
+        allowedTaker
+      )
       .accounts({
         maker: maker.publicKey,
         tokenMintA: offeredTokenMint,
@@ -236,7 +266,8 @@ describe("escrow", () => {
         // account addresses, that are specified in the program IDL, as they are
         // expected to reference the same programs for all the `makeOffer`
         // invocations.
-        tokenProgram: TOKEN_PROGRAM,
+        tokenProgram: tokenProgramA,
+        tokenProgramB,
       })
       .signers([maker])
       .rpc();
@@ -261,7 +292,7 @@ describe("escrow", () => {
       offeredTokenMint,
       offerAddress,
       true,
-      TOKEN_PROGRAM
+      tokenProgramA
     );
 
     return { offerAddress, vaultAddress };
@@ -269,7 +300,10 @@ describe("escrow", () => {
 
   const takeOfferTx = async (
     offerAddress: PublicKey,
-    taker: Keypair
+    taker: Keypair,
+    tokenAAmount: BN,
+    maxTokenBToPay: BN = U64_MAX,
+    minTokenAToReceive: BN = new BN(0)
   ): Promise<void> => {
     // `accounts` argument debugging tool.  Should be part of Anchor really.
     //
@@ -285,14 +319,30 @@ describe("escrow", () => {
     //   >
     // >;
 
+    // Offers that want lamports are taken without any token B accounts.
+    const offer = await program.account.offer.fetch(offerAddress);
+    const tokenBAccounts = offer.tokenMintB.equals(PublicKey.default)
+      ? {
+          tokenMintB: null,
+          takerTokenAccountB: null,
+          makerTokenAccountB: null,
+          treasuryTokenAccountB: null,
+          tokenProgramB: null,
+        }
+      : {
+          tokenMintB: offer.tokenMintB,
+          tokenProgramB: await getTokenProgram(offer.tokenMintB),
+        };
+
     const transactionSignature = await program.methods
-      .takeOffer()
-      .accounts({
+      .takeOffer(tokenAAmount, maxTokenBToPay, minTokenAToReceive)
+      .accountsPartial({
         taker: taker.publicKey,
         offer: offerAddress,
+        ...tokenBAccounts,
         // See note in the `makeOfferTx` on why this program address is provided
         // and the rest are not.
-        tokenProgram: TOKEN_PROGRAM,
+        tokenProgram: await getTokenProgram(offer.tokenMintA),
       })
       .signers([taker])
       .rpc();
@@ -300,6 +350,29 @@ describe("escrow", () => {
     await confirmTransaction(connection, transactionSignature);
   };
 
+  const refundExpiredOfferTx = async (
+    offerAddress: PublicKey,
+    keeper: Keypair
+  ): Promise<void> => {
+    const transactionSignature = await program.methods
+      .refundExpiredOffer()
+      .accounts({
+        keeper: keeper.publicKey,
+        offer: offerAddress,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .signers([keeper])
+      .rpc();
+
+    await confirmTransaction(connection, transactionSignature);
+  };
+
+  // The program checks expiry against the cluster clock, which can lag
+  // behind the local one.
+  const getClusterTime = async (): Promise<number> => {
+    return (await connection.getBlockTime(await connection.getSlot())) ?? 0;
+  };
+
   test("Offer created by Alice, vault holds the offer tokens", async () => {
     const offeredUsdc = new BN(10_000_000);
     const wantedWif = new BN(100_000_000);
@@ -350,7 +423,7 @@ describe("escrow", () => {
     expect(await getTokenBalance(bobUsdcAccount)).toEqual(new BN(20_000_000));
     expect(await getTokenBalance(bobWifAccount)).toEqual(new BN(300_000_000));
 
-    await takeOfferTx(offerAddress, bob);
+    await takeOfferTx(offerAddress, bob, new BN(10_000_000));
 
     expect(await getTokenBalance(aliceUsdcAccount)).toEqual(new BN(90_000_000));
     expect(await getTokenBalance(aliceWifAccount)).toEqual(new BN(105_000_000));
@@ -399,10 +472,769 @@ describe("escrow", () => {
     expect(await getTokenBalance(aliceUsdcAccount)).toEqual(new BN(90_000_000));
 
     try {
-      await takeOfferTx(offerAddress, bob);
+      await takeOfferTx(offerAddress, bob, offeredUsdc);
     } catch (err) {
       // Check if Bob couldn't take closed offer, and his tokens preserved
       expect(await getTokenBalance(bobWifAccount)).toEqual(new BN(200_000_000));
     }
   });
+
+  test("Offer partially taken by Bob, closed once the rest is taken", async () => {
+    const offeredUsdc = new BN(4_000_000);
+    const wantedWif = new BN(10_000_000);
+    const getTokenBalance = getTokenBalanceOn(connection);
+
+    const offerId = getRandomBigNumber();
+    const { offerAddress, vaultAddress } = await makeOfferTx(
+      alice,
+      offerId,
+      usdcMint.publicKey,
+      offeredUsdc,
+      wifMint.publicKey,
+      wantedWif
+    );
+
+    const aliceWifBefore = await getTokenBalance(aliceWifAccount);
+    const bobUsdcBefore = await getTokenBalance(bobUsdcAccount);
+    const bobWifBefore = await getTokenBalance(bobWifAccount);
+
+    // A quarter of the offer, paid for pro rata.
+    await takeOfferTx(offerAddress, bob, new BN(1_000_000));
+
+    const offerAccount = await program.account.offer.fetch(offerAddress);
+    expect(offerAccount.tokenAOfferedAmount).toEqual(offeredUsdc);
+    expect(offerAccount.tokenARemainingAmount).toEqual(new BN(3_000_000));
+    expect(offerAccount.tokenBRemainingAmount).toEqual(new BN(7_500_000));
+    expect(await getTokenBalance(vaultAddress)).toEqual(new BN(3_000_000));
+    expect(await getTokenBalance(bobUsdcAccount)).toEqual(
+      bobUsdcBefore.add(new BN(1_000_000))
+    );
+    expect(await getTokenBalance(bobWifAccount)).toEqual(
+      bobWifBefore.sub(new BN(2_500_000))
+    );
+
+    // Taking more than remains is rejected.
+    await expect(
+      takeOfferTx(offerAddress, bob, new BN(3_000_001))
+    ).rejects.toThrow(/InvalidFillAmount/);
+
+    await takeOfferTx(offerAddress, bob, new BN(3_000_000));
+
+    expect(await connection.getAccountInfo(offerAddress)).toEqual(null);
+    expect(await connection.getAccountInfo(vaultAddress)).toEqual(null);
+    expect(await getTokenBalance(aliceWifAccount)).toEqual(
+      aliceWifBefore.add(wantedWif)
+    );
+    expect(await getTokenBalance(bobUsdcAccount)).toEqual(
+      bobUsdcBefore.add(offeredUsdc)
+    );
+  });
+
+  test("Expired offer can't be taken, anyone can refund it to Alice", async () => {
+    const offeredUsdc = new BN(1_000_000);
+    const wantedWif = new BN(1_000_000);
+    const getTokenBalance = getTokenBalanceOn(connection);
+    const keeper = Keypair.generate();
+
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: keeper.publicKey,
+          lamports: LAMPORTS_PER_SOL,
+        })
+      )
+    );
+
+    // An expiry in the past is rejected.
+    await expect(
+      makeOfferTx(
+        alice,
+        getRandomBigNumber(),
+        usdcMint.publicKey,
+        offeredUsdc,
+        wifMint.publicKey,
+        wantedWif,
+        new BN((await getClusterTime()) - 1)
+      )
+    ).rejects.toThrow(/InvalidExpiry/);
+
+    const aliceUsdcBefore = await getTokenBalance(aliceUsdcAccount);
+    const expiresAt = (await getClusterTime()) + 3;
+    const { offerAddress, vaultAddress } = await makeOfferTx(
+      alice,
+      getRandomBigNumber(),
+      usdcMint.publicKey,
+      offeredUsdc,
+      wifMint.publicKey,
+      wantedWif,
+      new BN(expiresAt)
+    );
+
+    const offerAccount = await program.account.offer.fetch(offerAddress);
+    expect(offerAccount.expiresAt).toEqual(new BN(expiresAt));
+
+    await expect(refundExpiredOfferTx(offerAddress, keeper)).rejects.toThrow(
+      /OfferNotExpired/
+    );
+
+    while ((await getClusterTime()) < expiresAt) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+
+    await expect(
+      takeOfferTx(offerAddress, bob, offeredUsdc)
+    ).rejects.toThrow(/OfferExpired/);
+
+    const keeperSolBefore = await connection.getBalance(keeper.publicKey);
+    await refundExpiredOfferTx(offerAddress, keeper);
+
+    expect(await connection.getAccountInfo(offerAddress)).toEqual(null);
+    expect(await connection.getAccountInfo(vaultAddress)).toEqual(null);
+    expect(await getTokenBalance(aliceUsdcAccount)).toEqual(aliceUsdcBefore);
+
+    // The keeper reward is more than the transaction fee.
+    expect(await connection.getBalance(keeper.publicKey)).toBeGreaterThan(
+      keeperSolBefore
+    );
+  });
+
+  test("Private offer can only be taken by its taker, who can list it", async () => {
+    const offeredUsdc = new BN(1_000_000);
+    const wantedWif = new BN(1_000_000);
+    const getTokenBalance = getTokenBalanceOn(connection);
+    const carol = Keypair.generate();
+
+    const { offerAddress } = await makeOfferTx(
+      alice,
+      getRandomBigNumber(),
+      usdcMint.publicKey,
+      offeredUsdc,
+      wifMint.publicKey,
+      wantedWif,
+      null,
+      bob.publicKey
+    );
+
+    const offerAccount = await program.account.offer.fetch(offerAddress);
+    expect(offerAccount.allowedTaker).toEqual(bob.publicKey);
+
+    // `allowed_taker` follows the discriminator, the id, three keys and four
+    // amounts.
+    const offersFor = async (taker: PublicKey) =>
+      (
+        await program.account.offer.all([
+          {
+            memcmp: {
+              offset: 8 + 8 + 32 * 3 + 8 * 4,
+              bytes: anchor.utils.bytes.bs58.encode(
+                Buffer.concat([Buffer.from([1]), taker.toBuffer()])
+              ),
+            },
+          },
+        ])
+      ).map(({ publicKey }) => publicKey.toBase58());
+    expect(await offersFor(bob.publicKey)).toContain(offerAddress.toBase58());
+    expect(await offersFor(carol.publicKey)).not.toContain(
+      offerAddress.toBase58()
+    );
+
+    // Carol can pay, but is not the taker Alice chose.
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: provider.publicKey,
+          toPubkey: carol.publicKey,
+          lamports: LAMPORTS_PER_SOL,
+        }),
+        createAssociatedTokenAccountIdempotentInstruction(
+          provider.publicKey,
+          getAssociatedTokenAddressSync(
+            wifMint.publicKey,
+            carol.publicKey,
+            false,
+            TOKEN_PROGRAM
+          ),
+          carol.publicKey,
+          wifMint.publicKey,
+          TOKEN_PROGRAM
+        )
+      )
+    );
+    await expect(
+      takeOfferTx(offerAddress, carol, offeredUsdc)
+    ).rejects.toThrow(/WrongTaker/);
+
+    const bobUsdcBefore = await getTokenBalance(bobUsdcAccount);
+    await takeOfferTx(offerAddress, bob, offeredUsdc);
+    expect(await getTokenBalance(bobUsdcAccount)).toEqual(
+      bobUsdcBefore.add(offeredUsdc)
+    );
+  });
+
+  test("Offer updated by Alice keeps its address, repriced and resized", async () => {
+    const getTokenBalance = getTokenBalanceOn(connection);
+    const { offerAddress, vaultAddress } = await makeOfferTx(
+      alice,
+      getRandomBigNumber(),
+      usdcMint.publicKey,
+      new BN(2_000_000),
+      wifMint.publicKey,
+      new BN(4_000_000)
+    );
+    const aliceUsdcBefore = await getTokenBalance(aliceUsdcAccount);
+
+    const updateOfferTx = async (tokenAAmount: BN, tokenBWantedAmount: BN) => {
+      await program.methods
+        .updateOffer(tokenAAmount, tokenBWantedAmount)
+        .accounts({
+          maker: alice.publicKey,
+          offer: offerAddress,
+          tokenProgram: TOKEN_PROGRAM,
+        })
+        .signers([alice])
+        .rpc();
+    };
+
+    // Deposit 1 USDC more and ask for more WIF.
+    await updateOfferTx(new BN(3_000_000), new BN(9_000_000));
+    expect(await getTokenBalance(vaultAddress)).toEqual(new BN(3_000_000));
+    expect(await getTokenBalance(aliceUsdcAccount)).toEqual(
+      aliceUsdcBefore.sub(new BN(1_000_000))
+    );
+
+    // Withdraw 2 USDC, same price per token.
+    await updateOfferTx(new BN(1_000_000), new BN(3_000_000));
+    expect(await getTokenBalance(vaultAddress)).toEqual(new BN(1_000_000));
+    expect(await getTokenBalance(aliceUsdcAccount)).toEqual(
+      aliceUsdcBefore.add(new BN(1_000_000))
+    );
+
+    const offerAccount = await program.account.offer.fetch(offerAddress);
+    expect(offerAccount.tokenAOfferedAmount).toEqual(new BN(1_000_000));
+    expect(offerAccount.tokenBWantedAmount).toEqual(new BN(3_000_000));
+    expect(offerAccount.tokenARemainingAmount).toEqual(new BN(1_000_000));
+    expect(offerAccount.tokenBRemainingAmount).toEqual(new BN(3_000_000));
+
+    await expect(updateOfferTx(new BN(0), new BN(3_000_000))).rejects.toThrow(
+      /ZeroAmount/
+    );
+
+    // Only Alice may update her offer.
+    await expect(
+      program.methods
+        .updateOffer(new BN(1), new BN(1))
+        .accounts({
+          maker: bob.publicKey,
+          offer: offerAddress,
+          tokenProgram: TOKEN_PROGRAM,
+        })
+        .signers([bob])
+        .rpc()
+    ).rejects.toThrow(/ConstraintHasOne|ConstraintSeeds/);
+
+    const bobWifBefore = await getTokenBalance(bobWifAccount);
+    await takeOfferTx(offerAddress, bob, new BN(1_000_000));
+    expect(await getTokenBalance(bobWifAccount)).toEqual(
+      bobWifBefore.sub(new BN(3_000_000))
+    );
+  });
+
+  test("Take fails when Alice reprices the offer first", async () => {
+    const offeredUsdc = new BN(1_000_000);
+    const getTokenBalance = getTokenBalanceOn(connection);
+    const { offerAddress } = await makeOfferTx(
+      alice,
+      getRandomBigNumber(),
+      usdcMint.publicKey,
+      offeredUsdc,
+      wifMint.publicKey,
+      new BN(1_000_000)
+    );
+
+    // Bob sees the offer at 1 WIF, Alice doubles the price before his take
+    // lands.
+    const seen = await program.account.offer.fetch(offerAddress);
+    await program.methods
+      .updateOffer(offeredUsdc, new BN(2_000_000))
+      .accounts({
+        maker: alice.publicKey,
+        offer: offerAddress,
+        tokenProgram: TOKEN_PROGRAM,
+      })
+      .signers([alice])
+      .rpc();
+
+    const bobWifBefore = await getTokenBalance(bobWifAccount);
+    await expect(
+      takeOfferTx(
+        offerAddress,
+        bob,
+        offeredUsdc,
+        seen.tokenBRemainingAmount,
+        offeredUsdc
+      )
+    ).rejects.toThrow(/SlippageExceeded/);
+    await expect(
+      takeOfferTx(
+        offerAddress,
+        bob,
+        offeredUsdc,
+        new BN(2_000_000),
+        offeredUsdc.add(new BN(1))
+      )
+    ).rejects.toThrow(/SlippageExceeded/);
+    expect(await getTokenBalance(bobWifAccount)).toEqual(bobWifBefore);
+
+    await takeOfferTx(
+      offerAddress,
+      bob,
+      offeredUsdc,
+      new BN(2_000_000),
+      offeredUsdc
+    );
+    expect(await getTokenBalance(bobWifAccount)).toEqual(
+      bobWifBefore.sub(new BN(2_000_000))
+    );
+  });
+
+  const updateConfigTx = async (
+    admin: PublicKey,
+    feeBps: number,
+    signers: Array<Keypair> = []
+  ): Promise<void> => {
+    await program.methods
+      .updateConfig(admin, feeBps, treasury.publicKey)
+      .accounts({ admin })
+      .signers(signers)
+      .rpc();
+  };
+
+  test("Protocol fee of token B goes to the treasury", async () => {
+    const offeredUsdc = new BN(1_000_000);
+    const wantedWif = new BN(2_000_000);
+    const getTokenBalance = getTokenBalanceOn(connection);
+    const treasuryWifAccount = getAssociatedTokenAddressSync(
+      wifMint.publicKey,
+      treasury.publicKey,
+      false,
+      TOKEN_PROGRAM
+    );
+
+    // 2.5%
+    await updateConfigTx(provider.publicKey, 250);
+    try {
+      const config = await program.account.config.fetch(
+        PublicKey.findProgramAddressSync(
+          [Buffer.from("config")],
+          program.programId
+        )[0]
+      );
+      expect(config.feeBps).toEqual(250);
+      expect(config.treasury).toEqual(treasury.publicKey);
+
+      const { offerAddress } = await makeOfferTx(
+        alice,
+        getRandomBigNumber(),
+        usdcMint.publicKey,
+        offeredUsdc,
+        wifMint.publicKey,
+        wantedWif
+      );
+      const aliceWifBefore = await getTokenBalance(aliceWifAccount);
+      const bobWifBefore = await getTokenBalance(bobWifAccount);
+
+      await takeOfferTx(offerAddress, bob, offeredUsdc);
+
+      // Bob pays the price, the fee comes out of what Alice receives.
+      expect(await getTokenBalance(bobWifAccount)).toEqual(
+        bobWifBefore.sub(wantedWif)
+      );
+      expect(await getTokenBalance(treasuryWifAccount)).toEqual(
+        new BN(50_000)
+      );
+      expect(await getTokenBalance(aliceWifAccount)).toEqual(
+        aliceWifBefore.add(new BN(1_950_000))
+      );
+    } finally {
+      await updateConfigTx(provider.publicKey, 0);
+    }
+  });
+
+  test("Offer wanting SOL is paid in lamports, fee included", async () => {
+    const offeredUsdc = new BN(1_000_000);
+    const wantedLamports = new BN(LAMPORTS_PER_SOL);
+    const getTokenBalance = getTokenBalanceOn(connection);
+    const getBalance = async (address: PublicKey) =>
+      new BN(await connection.getBalance(address));
+
+    // 2.5%
+    await updateConfigTx(provider.publicKey, 250);
+    try {
+      const { offerAddress } = await makeOfferTx(
+        alice,
+        getRandomBigNumber(),
+        usdcMint.publicKey,
+        offeredUsdc,
+        null,
+        wantedLamports
+      );
+      const offer = await program.account.offer.fetch(offerAddress);
+      expect(offer.tokenMintB).toEqual(PublicKey.default);
+
+      const bobUsdcBefore = await getTokenBalance(bobUsdcAccount);
+      const aliceBefore = await getBalance(alice.publicKey);
+      const treasuryBefore = await getBalance(treasury.publicKey);
+      const offerRent = await getBalance(offerAddress);
+
+      await takeOfferTx(offerAddress, bob, offeredUsdc);
+
+      expect(await getTokenBalance(bobUsdcAccount)).toEqual(
+        bobUsdcBefore.add(offeredUsdc)
+      );
+      expect(await getBalance(treasury.publicKey)).toEqual(
+        treasuryBefore.add(new BN(25_000_000))
+      );
+      // Alice also gets the rent of the closed offer back.
+      expect(await getBalance(alice.publicKey)).toEqual(
+        aliceBefore.add(new BN(975_000_000)).add(offerRent)
+      );
+    } finally {
+      await updateConfigTx(provider.publicKey, 0);
+    }
+  });
+
+  describe("offered and wanted mints of different token programs", () => {
+    const tokenPrograms = new Map([
+      ["Token", TOKEN_PROGRAM_ID],
+      ["Token-2022", TOKEN_2022_PROGRAM_ID],
+    ]);
+
+    // An offered mint of Alice and a wanted mint of Bob for each program.
+    const mints = new Map(
+      [...tokenPrograms.keys()].map((name) => [name, makeKeypairs(2)])
+    );
+
+    beforeAll(async () => {
+      for (const [name, tokenProgram] of tokenPrograms) {
+        const [offeredMint, wantedMint] = mints.get(name)!;
+        const tx = new Transaction();
+        tx.instructions = [
+          ...(await createTokenAndMintTo(
+            connection,
+            provider.publicKey,
+            offeredMint.publicKey,
+            6,
+            alice.publicKey,
+            [{ recepient: alice.publicKey, amount: 10_000_000 }],
+            tokenProgram
+          )),
+          ...(await createTokenAndMintTo(
+            connection,
+            provider.publicKey,
+            wantedMint.publicKey,
+            6,
+            bob.publicKey,
+            [{ recepient: bob.publicKey, amount: 10_000_000 }],
+            tokenProgram
+          )),
+        ];
+        await provider.sendAndConfirm(tx, [
+          alice,
+          bob,
+          offeredMint,
+          wantedMint,
+        ]);
+      }
+    });
+
+    test.each(
+      [...tokenPrograms.keys()].flatMap((offeredProgram) =>
+        [...tokenPrograms.keys()].map((wantedProgram) => [
+          offeredProgram,
+          wantedProgram,
+        ])
+      )
+    )("%s for %s", async (offeredProgram, wantedProgram) => {
+      const offeredAmount = new BN(1_000_000);
+      const wantedAmount = new BN(2_000_000);
+      // Bob and Alice only get these accounts from the first take of a mint.
+      const getTokenBalance = (address: PublicKey) =>
+        getTokenBalanceOn(connection)(address).catch(() => new BN(0));
+      const [offeredMint] = mints.get(offeredProgram)!;
+      const [, wantedMint] = mints.get(wantedProgram)!;
+      const bobOfferedAccount = getAssociatedTokenAddressSync(
+        offeredMint.publicKey,
+        bob.publicKey,
+        false,
+        tokenPrograms.get(offeredProgram)
+      );
+      const aliceWantedAccount = getAssociatedTokenAddressSync(
+        wantedMint.publicKey,
+        alice.publicKey,
+        false,
+        tokenPrograms.get(wantedProgram)
+      );
+
+      const { offerAddress, vaultAddress } = await makeOfferTx(
+        alice,
+        getRandomBigNumber(),
+        offeredMint.publicKey,
+        offeredAmount,
+        wantedMint.publicKey,
+        wantedAmount
+      );
+      expect(await getTokenBalance(vaultAddress)).toEqual(offeredAmount);
+
+      const bobOfferedBefore = await getTokenBalance(bobOfferedAccount);
+      const aliceWantedBefore = await getTokenBalance(aliceWantedAccount);
+
+      await takeOfferTx(offerAddress, bob, offeredAmount);
+
+      expect(await getTokenBalance(bobOfferedAccount)).toEqual(
+        bobOfferedBefore.add(offeredAmount)
+      );
+      expect(await getTokenBalance(aliceWantedAccount)).toEqual(
+        aliceWantedBefore.add(wantedAmount)
+      );
+      expect(await connection.getAccountInfo(offerAddress)).toEqual(null);
+    });
+  });
+
+  describe("rejects", () => {
+    const offeredUsdc = new BN(1_000_000);
+    const wantedWif = new BN(1_000_000);
+
+    test("zero amounts", async () => {
+      await expect(
+        makeOfferTx(
+          alice,
+          getRandomBigNumber(),
+          usdcMint.publicKey,
+          new BN(0),
+          wifMint.publicKey,
+          wantedWif
+        )
+      ).rejects.toThrow(/ZeroAmount/);
+      await expect(
+        makeOfferTx(
+          alice,
+          getRandomBigNumber(),
+          usdcMint.publicKey,
+          offeredUsdc,
+          wifMint.publicKey,
+          new BN(0)
+        )
+      ).rejects.toThrow(/ZeroAmount/);
+
+      const { offerAddress } = await makeOfferTx(
+        alice,
+        getRandomBigNumber(),
+        usdcMint.publicKey,
+        offeredUsdc,
+        wifMint.publicKey,
+        wantedWif
+      );
+      await expect(takeOfferTx(offerAddress, bob, new BN(0))).rejects.toThrow(
+        /ZeroAmount/
+      );
+    });
+
+    test("identical mints", async () => {
+      await expect(
+        makeOfferTx(
+          alice,
+          getRandomBigNumber(),
+          usdcMint.publicKey,
+          offeredUsdc,
+          usdcMint.publicKey,
+          wantedWif
+        )
+      ).rejects.toThrow(/IdenticalMints/);
+    });
+
+    test("maker taking their own offer", async () => {
+      const { offerAddress } = await makeOfferTx(
+        alice,
+        getRandomBigNumber(),
+        usdcMint.publicKey,
+        offeredUsdc,
+        wifMint.publicKey,
+        wantedWif
+      );
+      await expect(
+        takeOfferTx(offerAddress, alice, offeredUsdc)
+      ).rejects.toThrow(/WrongTaker/);
+    });
+
+    test("offered mint that takes a transfer fee", async () => {
+      // The vault receives the offered amount less the fee.
+      const feeMint = Keypair.generate();
+      const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+      const aliceFeeAccount = getAssociatedTokenAddressSync(
+        feeMint.publicKey,
+        alice.publicKey,
+        false,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const tx = new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: feeMint.publicKey,
+          lamports: await connection.getMinimumBalanceForRentExemption(mintLen),
+          space: mintLen,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          feeMint.publicKey,
+          alice.publicKey,
+          alice.publicKey,
+          100,
+          BigInt(1_000_000),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMint2Instruction(
+          feeMint.publicKey,
+          6,
+          alice.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createAssociatedTokenAccountIdempotentInstruction(
+          provider.publicKey,
+          aliceFeeAccount,
+          alice.publicKey,
+          feeMint.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createMintToInstruction(
+          feeMint.publicKey,
+          aliceFeeAccount,
+          alice.publicKey,
+          10_000_000,
+          [],
+          TOKEN_2022_PROGRAM_ID
+        )
+      );
+      await provider.sendAndConfirm(tx, [feeMint, alice]);
+
+      await expect(
+        makeOfferTx(
+          alice,
+          getRandomBigNumber(),
+          feeMint.publicKey,
+          offeredUsdc,
+          wifMint.publicKey,
+          wantedWif
+        )
+      ).rejects.toThrow(/VaultAmountMismatch/);
+    });
+
+    test("closing someone else's offer", async () => {
+      const { offerAddress } = await makeOfferTx(
+        alice,
+        getRandomBigNumber(),
+        usdcMint.publicKey,
+        offeredUsdc,
+        wifMint.publicKey,
+        wantedWif
+      );
+      await expect(
+        program.methods
+          .closeOffer()
+          .accounts({
+            maker: bob.publicKey,
+            offer: offerAddress,
+            tokenProgram: TOKEN_PROGRAM,
+          })
+          .signers([bob])
+          .rpc()
+      ).rejects.toThrow(/ConstraintHasOne|ConstraintSeeds/);
+      expect(await connection.getAccountInfo(offerAddress)).not.toEqual(null);
+    });
+
+    test("forged offer accounts", async () => {
+      const { offerAddress } = await makeOfferTx(
+        alice,
+        getRandomBigNumber(),
+        usdcMint.publicKey,
+        offeredUsdc,
+        wifMint.publicKey,
+        wantedWif
+      );
+
+      // Alice's offer with Bob as its maker, so Bob would be paid.
+      await expect(
+        program.methods
+          .takeOffer(offeredUsdc, U64_MAX, new BN(0))
+          .accountsPartial({
+            taker: bob.publicKey,
+            maker: bob.publicKey,
+            offer: offerAddress,
+            tokenMintB: wifMint.publicKey,
+            tokenProgram: TOKEN_PROGRAM,
+            tokenProgramB: TOKEN_PROGRAM,
+          })
+          .signers([bob])
+          .rpc()
+      ).rejects.toThrow(/ConstraintHasOne|ConstraintSeeds/);
+
+      // An account that is not an offer at all.
+      await expect(
+        program.methods
+          .takeOffer(offeredUsdc, U64_MAX, new BN(0))
+          .accountsPartial({
+            taker: bob.publicKey,
+            maker: alice.publicKey,
+            offer: aliceUsdcAccount,
+            tokenMintB: wifMint.publicKey,
+            tokenProgram: TOKEN_PROGRAM,
+            tokenProgramB: TOKEN_PROGRAM,
+          })
+          .signers([bob])
+          .rpc()
+      ).rejects.toThrow(/AccountOwnedByWrongProgram/);
+    });
+
+    test("token B accounts for an offer that wants SOL", async () => {
+      const { offerAddress } = await makeOfferTx(
+        alice,
+        getRandomBigNumber(),
+        usdcMint.publicKey,
+        offeredUsdc,
+        null,
+        new BN(LAMPORTS_PER_SOL)
+      );
+
+      await expect(
+        program.methods
+          .takeOffer(offeredUsdc, U64_MAX, new BN(0))
+          .accountsPartial({
+            taker: bob.publicKey,
+            offer: offerAddress,
+            tokenMintB: wifMint.publicKey,
+            tokenProgram: TOKEN_PROGRAM,
+            tokenProgramB: TOKEN_PROGRAM,
+          })
+          .signers([bob])
+          .rpc()
+      ).rejects.toThrow(/WrongTokenB/);
+    });
+
+    test("config changes by anyone but the admin", async () => {
+      await expect(
+        updateConfigTx(bob.publicKey, 10_000, [bob])
+      ).rejects.toThrow(/Unauthorized/);
+    });
+
+    test("fees over 100%", async () => {
+      await expect(
+        updateConfigTx(provider.publicKey, 10_001)
+      ).rejects.toThrow(/InvalidFee/);
+    });
+
+    // `ArithmeticOverflow` guards the price and fee math and the remaining
+    // amounts, both are computed in u128 and never exceed what is paid, so no
+    // valid offer reaches it.
+  });
 });
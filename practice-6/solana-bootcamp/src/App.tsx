@@ -16,7 +16,7 @@ import { clusterApiUrl } from "@solana/web3.js";
 import { WalletAdapterNetwork } from "@solana/wallet-adapter-base";
 
 import { EscrowProgram } from "@/solana-service/program";
-import { Wallet } from "@coral-xyz/anchor";
+import { BN, Wallet } from "@coral-xyz/anchor";
 import { OffersPage } from "@/pages/all-offers";
 import { OpenOffersPage } from "@/pages/open-offers";
 import AccountOffers from "@/pages/account-offers";
@@ -86,7 +86,7 @@ const App: React.FC = () => {
     }
   };
 
-  const onTakeOffer = async () => {
+  const onTakeOffer = async (tokenAAmount: BN, maxTokenBToPay: BN) => {
     setLoading(true);
     if (!isWalletConnected) {
       select(wallets[0].adapter.name);
@@ -104,12 +104,15 @@ const App: React.FC = () => {
         new PublicKey(selectedOffer?.acctMaker),
         new PublicKey(selectedOffer?.acctOffer),
         new PublicKey(selectedOffer?.acctTokenMintA),
-        new PublicKey(selectedOffer?.acctTokenMintB)
+        new PublicKey(selectedOffer?.acctTokenMintB),
+        tokenAAmount,
+        maxTokenBToPay
       );
     } catch (e) {
       toast.error("Error taking offer");
     } finally {
       await queryClient.invalidateQueries({ queryKey: ["offers"] });
+      await queryClient.invalidateQueries({ queryKey: ["remainingAmounts"] });
       setLoading(false);
     }
   };
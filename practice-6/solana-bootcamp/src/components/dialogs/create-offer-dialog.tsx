@@ -27,7 +27,7 @@ import { WalletAdapterNetwork } from "@solana/wallet-adapter-base";
 import { useAnchorWallet, useWallet } from "@solana/wallet-adapter-react";
 
 import { Wallet } from "@coral-xyz/anchor";
-import { METADATA } from "@/utils";
+import { METADATA, NATIVE_SOL } from "@/utils";
 import { useQueryClient } from "@tanstack/react-query";
 import { useMetadata } from "@/hooks/useMetadata";
 
@@ -192,6 +192,7 @@ export function CreateOfferDialog({
                 variant="ghost"
                 size="icon"
                 onClick={swapTokens}
+                disabled={formData.tokenB === NATIVE_SOL.address}
                 className="rounded-full h-8 w-8"
               >
                 <ArrowDownUp className="h-4 w-4" />
@@ -210,7 +211,11 @@ export function CreateOfferDialog({
                     <SelectValue placeholder="Select token" />
                   </SelectTrigger>
                   <SelectContent>
-                    {Object.entries(METADATA).map(([address, metadata]) => (
+                    {/* Offers may want SOL, but only tokens can be offered. */}
+                    {[
+                      ...Object.entries(METADATA),
+                      [NATIVE_SOL.address, NATIVE_SOL] as const,
+                    ].map(([address, metadata]) => (
                       <SelectItem key={address} value={address}>
                         <div className="flex items-center gap-2">
                           <span>{metadata.icon}</span>
@@ -10,6 +10,8 @@ import {
 import { Button } from "@/components/ui/button";
 import { Loader2 } from "lucide-react";
 import { useMetadata } from "@/hooks/useMetadata";
+import { useRemainingAmounts } from "@/hooks/useOffer";
+import { BN } from "@coral-xyz/anchor";
 
 import { Offer } from "@/types/offer";
 
@@ -25,7 +27,7 @@ export default function TakeOfferDialog({
   setSelectedOffer: (offer: Offer | null) => void;
   isWalletConnected: boolean;
   connectWallet: () => void;
-  onTakeOffer: () => void;
+  onTakeOffer: (tokenAAmount: BN, maxTokenBToPay: BN) => void;
   loading: boolean;
 }) {
   const { data: firstTokenMetadata } = useMetadata(
@@ -34,6 +36,10 @@ export default function TakeOfferDialog({
   const { data: secondTokenMetadata } = useMetadata(
     selectedOffer?.acctTokenMintB
   );
+  // The take is limited to exactly these amounts, so they are what is shown.
+  const { data: remaining } = useRemainingAmounts(selectedOffer?.acctOffer);
+  const tokenAAmount = remaining?.tokenAAmount.toString() ?? "…";
+  const tokenBAmount = remaining?.tokenBAmount.toString() ?? "…";
 
   return (
     <Dialog
@@ -46,7 +52,7 @@ export default function TakeOfferDialog({
           <DialogDescription>
             {!isWalletConnected
               ? "Connect your wallet to take this offer."
-              : `You're about to exchange ${selectedOffer?.tokenAOfferedAmount} ${firstTokenMetadata?.symbol} for ${selectedOffer?.tokenBWantedAmount} ${secondTokenMetadata?.symbol}.`}
+              : `You're about to exchange ${tokenAAmount} ${firstTokenMetadata?.symbol} for ${tokenBAmount} ${secondTokenMetadata?.symbol}.`}
           </DialogDescription>
         </DialogHeader>
 
@@ -56,7 +62,7 @@ export default function TakeOfferDialog({
               <div className="text-center">
                 <div className="text-2xl mb-1">{firstTokenMetadata?.icon}</div>
                 <div className="font-medium">
-                  {selectedOffer.tokenAOfferedAmount.toString()}{" "}
+                  {tokenAAmount}{" "}
                   {firstTokenMetadata?.symbol}
                 </div>
               </div>
@@ -64,7 +70,7 @@ export default function TakeOfferDialog({
               <div className="text-center">
                 <div className="text-2xl mb-1">{secondTokenMetadata?.icon}</div>
                 <div className="font-medium">
-                  {selectedOffer.tokenBWantedAmount.toString()}{" "}
+                  {tokenBAmount}{" "}
                   {secondTokenMetadata?.symbol}
                 </div>
               </div>
@@ -89,7 +95,14 @@ export default function TakeOfferDialog({
               )}
             </Button>
           ) : (
-            <Button onClick={onTakeOffer} disabled={loading} className="w-full">
+            <Button
+              onClick={() =>
+                remaining &&
+                onTakeOffer(remaining.tokenAAmount, remaining.tokenBAmount)
+              }
+              disabled={loading || !remaining}
+              className="w-full"
+            >
               {loading ? (
                 <>
                   <Loader2 className="mr-2 h-4 w-4 animate-spin" />
@@ -8,6 +8,7 @@ import {
 } from "@solana/spl-token";
 import { Metaplex } from "@metaplex-foundation/js";
 import { ENV, TokenListProvider } from "@solana/spl-token-registry";
+import { NATIVE_SOL } from "@/utils";
 
 interface TokenMetadata {
   address: string;
@@ -39,6 +40,8 @@ const DUMMY_TOKENS: Record<string, TokenMetadata> = {
     decimals: 9,
     icon: "https://raw.githubusercontent.com/solana-labs/token-list/main/assets/mainnet/So11111111111111111111111111111111111111112/logo.png",
   },
+  // Native SOL wanted by an offer, it has no mint.
+  [NATIVE_SOL.address]: { ...NATIVE_SOL, name: "Solana", decimals: 9 },
   EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v: {
     address: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
     symbol: "USDC",
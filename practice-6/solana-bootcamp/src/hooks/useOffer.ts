@@ -0,0 +1,29 @@
+import { PublicKey } from "@solana/web3.js";
+import { useConnection } from "@solana/wallet-adapter-react";
+import { useQuery } from "@tanstack/react-query";
+import { fetchOffer } from "@/solana-service/program";
+
+// What remains of an offer after partial fills, read from its account as the
+// indexer only knows the original amounts.
+export function useRemainingAmounts(offerAddress?: string) {
+  const { connection } = useConnection();
+
+  return useQuery({
+    queryKey: ["remainingAmounts", offerAddress],
+    queryFn: async () => {
+      if (!offerAddress) {
+        throw new Error("Offer address is required");
+      }
+
+      const offer = await fetchOffer(connection, new PublicKey(offerAddress));
+
+      return {
+        tokenAAmount: offer.tokenARemainingAmount,
+        tokenBAmount: offer.tokenBRemainingAmount,
+      };
+    },
+    enabled: !!offerAddress,
+    // Always read the offer again, a take is only as good as these amounts.
+    staleTime: 0,
+  });
+}
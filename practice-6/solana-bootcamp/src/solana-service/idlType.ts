@@ -13,24 +13,522 @@ export type Escrow = {
     description: "Created with Anchor";
   };
   instructions: [
+    {
+      name: "closeOffer";
+      discriminator: [191, 72, 67, 35, 239, 209, 97, 132];
+      accounts: [
+        {
+          name: "maker";
+          writable: true;
+          signer: true;
+          relations: ["offer"];
+        },
+        {
+          name: "offer";
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "const";
+                value: [111, 102, 102, 101, 114];
+              },
+              {
+                kind: "account";
+                path: "maker";
+              },
+              {
+                kind: "account";
+                path: "offer.id";
+                account: "offer";
+              }
+            ];
+          };
+        },
+        {
+          name: "vault";
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "offer";
+              },
+              {
+                kind: "account";
+                path: "tokenProgram";
+              },
+              {
+                kind: "account";
+                path: "tokenMintA";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
+        },
+        {
+          name: "tokenMintA";
+          relations: ["offer"];
+        },
+        {
+          name: "makerTokenAccountA";
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "maker";
+              },
+              {
+                kind: "account";
+                path: "tokenProgram";
+              },
+              {
+                kind: "account";
+                path: "tokenMintA";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
+        },
+        {
+          name: "associatedTokenProgram";
+          address: "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+        },
+        {
+          name: "tokenProgram";
+        },
+        {
+          name: "systemProgram";
+          address: "11111111111111111111111111111111";
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "initializeConfig";
+      discriminator: [208, 127, 21, 1, 194, 190, 196, 70];
+      accounts: [
+        {
+          name: "admin";
+          writable: true;
+          signer: true;
+        },
+        {
+          name: "config";
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "const";
+                value: [99, 111, 110, 102, 105, 103];
+              }
+            ];
+          };
+        },
+        {
+          name: "program";
+          address: "4g5EN9Sk7wEcZqfjdjDtvq7T9u5YUrBKTe23fVJoL8yy";
+        },
+        {
+          name: "programData";
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "program";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                2, 168, 246, 145, 78, 136, 161, 176, 226, 16, 21, 62, 247, 99,
+                174, 43, 0, 194, 185, 61, 22, 193, 36, 210, 192, 83, 122, 16, 4,
+                128, 0, 0
+              ];
+            };
+          };
+        },
+        {
+          name: "systemProgram";
+          address: "11111111111111111111111111111111";
+        }
+      ];
+      args: [
+        {
+          name: "feeBps";
+          type: "u16";
+        },
+        {
+          name: "treasury";
+          type: "pubkey";
+        }
+      ];
+    },
     {
       name: "makeOffer";
       discriminator: [214, 98, 97, 35, 59, 12, 44, 178];
       accounts: [
         {
-          name: "maker";
+          name: "maker";
+          writable: true;
+          signer: true;
+        },
+        {
+          name: "tokenMintA";
+        },
+        {
+          name: "tokenMintB";
+          optional: true;
+        },
+        {
+          name: "makerTokenAccountA";
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "maker";
+              },
+              {
+                kind: "account";
+                path: "tokenProgram";
+              },
+              {
+                kind: "account";
+                path: "tokenMintA";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
+        },
+        {
+          name: "offer";
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "const";
+                value: [111, 102, 102, 101, 114];
+              },
+              {
+                kind: "account";
+                path: "maker";
+              },
+              {
+                kind: "arg";
+                path: "id";
+              }
+            ];
+          };
+        },
+        {
+          name: "vault";
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "offer";
+              },
+              {
+                kind: "account";
+                path: "tokenProgram";
+              },
+              {
+                kind: "account";
+                path: "tokenMintA";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
+        },
+        {
+          name: "associatedTokenProgram";
+          address: "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+        },
+        {
+          name: "tokenProgram";
+        },
+        {
+          name: "tokenProgramB";
+          optional: true;
+        },
+        {
+          name: "systemProgram";
+          address: "11111111111111111111111111111111";
+        }
+      ];
+      args: [
+        {
+          name: "id";
+          type: "u64";
+        },
+        {
+          name: "tokenAOfferedAmount";
+          type: "u64";
+        },
+        {
+          name: "tokenBWantedAmount";
+          type: "u64";
+        },
+        {
+          name: "expiresAt";
+          type: {
+            option: "i64";
+          };
+        },
+        {
+          name: "allowedTaker";
+          type: {
+            option: "pubkey";
+          };
+        }
+      ];
+    },
+    {
+      name: "refundExpiredOffer";
+      discriminator: [201, 18, 52, 83, 130, 54, 225, 87];
+      accounts: [
+        {
+          name: "keeper";
+          writable: true;
+          signer: true;
+        },
+        {
+          name: "maker";
+          writable: true;
+          relations: ["offer"];
+        },
+        {
+          name: "offer";
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "const";
+                value: [111, 102, 102, 101, 114];
+              },
+              {
+                kind: "account";
+                path: "maker";
+              },
+              {
+                kind: "account";
+                path: "offer.id";
+                account: "offer";
+              }
+            ];
+          };
+        },
+        {
+          name: "vault";
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "offer";
+              },
+              {
+                kind: "account";
+                path: "tokenProgram";
+              },
+              {
+                kind: "account";
+                path: "tokenMintA";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
+        },
+        {
+          name: "tokenMintA";
+          relations: ["offer"];
+        },
+        {
+          name: "makerTokenAccountA";
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "maker";
+              },
+              {
+                kind: "account";
+                path: "tokenProgram";
+              },
+              {
+                kind: "account";
+                path: "tokenMintA";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
+        },
+        {
+          name: "associatedTokenProgram";
+          address: "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+        },
+        {
+          name: "tokenProgram";
+        },
+        {
+          name: "systemProgram";
+          address: "11111111111111111111111111111111";
+        }
+      ];
+      args: [];
+    },
+    {
+      name: "takeOffer";
+      discriminator: [128, 156, 242, 207, 237, 192, 103, 240];
+      accounts: [
+        {
+          name: "taker";
           writable: true;
           signer: true;
         },
+        {
+          name: "maker";
+          writable: true;
+          relations: ["offer"];
+        },
         {
           name: "tokenMintA";
+          relations: ["offer"];
         },
         {
           name: "tokenMintB";
+          optional: true;
         },
         {
-          name: "makerTokenAccountA";
+          name: "takerTokenAccountA";
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "taker";
+              },
+              {
+                kind: "account";
+                path: "tokenProgram";
+              },
+              {
+                kind: "account";
+                path: "tokenMintA";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
+        },
+        {
+          name: "takerTokenAccountB";
+          writable: true;
+          optional: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "taker";
+              },
+              {
+                kind: "account";
+                path: "tokenProgramB";
+              },
+              {
+                kind: "account";
+                path: "tokenMintB";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
+        },
+        {
+          name: "makerTokenAccountB";
           writable: true;
+          optional: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "maker";
+              },
+              {
+                kind: "account";
+                path: "tokenProgramB";
+              },
+              {
+                kind: "account";
+                path: "tokenMintB";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
         },
         {
           name: "offer";
@@ -46,8 +544,9 @@ export type Escrow = {
                 path: "maker";
               },
               {
-                kind: "arg";
-                path: "id";
+                kind: "account";
+                path: "offer.id";
+                account: "offer";
               }
             ];
           };
@@ -55,6 +554,75 @@ export type Escrow = {
         {
           name: "vault";
           writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "offer";
+              },
+              {
+                kind: "account";
+                path: "tokenProgram";
+              },
+              {
+                kind: "account";
+                path: "tokenMintA";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
+        },
+        {
+          name: "config";
+          pda: {
+            seeds: [
+              {
+                kind: "const";
+                value: [99, 111, 110, 102, 105, 103];
+              }
+            ];
+          };
+        },
+        {
+          name: "treasury";
+          writable: true;
+          relations: ["config"];
+        },
+        {
+          name: "treasuryTokenAccountB";
+          writable: true;
+          optional: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "treasury";
+              },
+              {
+                kind: "account";
+                path: "tokenProgramB";
+              },
+              {
+                kind: "account";
+                path: "tokenMintB";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
         },
         {
           name: "associatedTokenProgram";
@@ -63,6 +631,10 @@ export type Escrow = {
         {
           name: "tokenProgram";
         },
+        {
+          name: "tokenProgramB";
+          optional: true;
+        },
         {
           name: "systemProgram";
           address: "11111111111111111111111111111111";
@@ -70,52 +642,65 @@ export type Escrow = {
       ];
       args: [
         {
-          name: "id";
+          name: "tokenAAmount";
           type: "u64";
         },
         {
-          name: "tokenAOfferedAmount";
+          name: "maxTokenBToPay";
           type: "u64";
         },
         {
-          name: "tokenBWantedAmount";
+          name: "minTokenAToReceive";
           type: "u64";
         }
       ];
     },
     {
-      name: "takeOffer";
-      discriminator: [128, 156, 242, 207, 237, 192, 103, 240];
+      name: "updateConfig";
+      discriminator: [29, 158, 252, 191, 10, 83, 219, 99];
       accounts: [
         {
-          name: "taker";
-          writable: true;
+          name: "admin";
           signer: true;
+          relations: ["config"];
         },
         {
-          name: "maker";
+          name: "config";
           writable: true;
-          relations: ["offer"];
-        },
-        {
-          name: "tokenMintA";
-          relations: ["offer"];
-        },
+          pda: {
+            seeds: [
+              {
+                kind: "const";
+                value: [99, 111, 110, 102, 105, 103];
+              }
+            ];
+          };
+        }
+      ];
+      args: [
         {
-          name: "tokenMintB";
-          relations: ["offer"];
+          name: "admin";
+          type: "pubkey";
         },
         {
-          name: "takerTokenAccountA";
-          writable: true;
+          name: "feeBps";
+          type: "u16";
         },
         {
-          name: "takerTokenAccountB";
-          writable: true;
-        },
+          name: "treasury";
+          type: "pubkey";
+        }
+      ];
+    },
+    {
+      name: "updateOffer";
+      discriminator: [191, 70, 15, 66, 224, 2, 249, 223];
+      accounts: [
         {
-          name: "makerTokenAccountB";
+          name: "maker";
           writable: true;
+          signer: true;
+          relations: ["offer"];
         },
         {
           name: "offer";
@@ -141,6 +726,62 @@ export type Escrow = {
         {
           name: "vault";
           writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "offer";
+              },
+              {
+                kind: "account";
+                path: "tokenProgram";
+              },
+              {
+                kind: "account";
+                path: "tokenMintA";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
+        },
+        {
+          name: "tokenMintA";
+          relations: ["offer"];
+        },
+        {
+          name: "makerTokenAccountA";
+          writable: true;
+          pda: {
+            seeds: [
+              {
+                kind: "account";
+                path: "maker";
+              },
+              {
+                kind: "account";
+                path: "tokenProgram";
+              },
+              {
+                kind: "account";
+                path: "tokenMintA";
+              }
+            ];
+            program: {
+              kind: "const";
+              value: [
+                140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142,
+                13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216,
+                219, 233, 248, 89
+              ];
+            };
+          };
         },
         {
           name: "associatedTokenProgram";
@@ -154,10 +795,23 @@ export type Escrow = {
           address: "11111111111111111111111111111111";
         }
       ];
-      args: [];
+      args: [
+        {
+          name: "tokenAAmount";
+          type: "u64";
+        },
+        {
+          name: "tokenBWantedAmount";
+          type: "u64";
+        }
+      ];
     }
   ];
   accounts: [
+    {
+      name: "config";
+      discriminator: [155, 12, 170, 224, 30, 250, 204, 130];
+    },
     {
       name: "offer";
       discriminator: [215, 88, 60, 71, 170, 162, 73, 229];
@@ -166,11 +820,95 @@ export type Escrow = {
   errors: [
     {
       code: 6000;
-      name: "customError";
-      msg: "Custom error message";
+      name: "zeroAmount";
+      msg: "Amount must be more than zero";
+    },
+    {
+      code: 6001;
+      name: "identicalMints";
+      msg: "Offered and wanted mints must differ";
+    },
+    {
+      code: 6002;
+      name: "invalidFillAmount";
+      msg: "Fill amount is more than what remains of the offer";
+    },
+    {
+      code: 6003;
+      name: "arithmeticOverflow";
+      msg: "Arithmetic overflow";
+    },
+    {
+      code: 6004;
+      name: "invalidExpiry";
+      msg: "Expiry must be in the future";
+    },
+    {
+      code: 6005;
+      name: "offerExpired";
+      msg: "Offer has expired";
+    },
+    {
+      code: 6006;
+      name: "offerNotExpired";
+      msg: "Offer has not expired yet";
+    },
+    {
+      code: 6007;
+      name: "wrongTaker";
+      msg: "Taker is not allowed to take this offer";
+    },
+    {
+      code: 6008;
+      name: "vaultAmountMismatch";
+      msg: "Vault does not hold the amount the offer expects";
+    },
+    {
+      code: 6009;
+      name: "unauthorized";
+      msg: "Signer is not allowed to change the config";
+    },
+    {
+      code: 6010;
+      name: "invalidFee";
+      msg: "Fee must be at most 10000 basis points";
+    },
+    {
+      code: 6011;
+      name: "slippageExceeded";
+      msg: "Offer no longer matches the amounts the taker expects";
+    },
+    {
+      code: 6012;
+      name: "wrongTokenB";
+      msg: "Token B accounts do not match what the offer wants";
     }
   ];
   types: [
+    {
+      name: "config";
+      type: {
+        kind: "struct";
+        fields: [
+          {
+            name: "admin";
+            type: "pubkey";
+          },
+          {
+            name: "feeBps";
+            type: "u16";
+          },
+          {
+            name: "treasury";
+            type: "pubkey";
+          },
+          {
+            name: "bump";
+            type: "u8";
+          }
+        ];
+      };
+    },
     {
       name: "offer";
       type: {
@@ -192,10 +930,34 @@ export type Escrow = {
             name: "tokenMintB";
             type: "pubkey";
           },
+          {
+            name: "tokenAOfferedAmount";
+            type: "u64";
+          },
           {
             name: "tokenBWantedAmount";
             type: "u64";
           },
+          {
+            name: "tokenARemainingAmount";
+            type: "u64";
+          },
+          {
+            name: "tokenBRemainingAmount";
+            type: "u64";
+          },
+          {
+            name: "allowedTaker";
+            type: {
+              option: "pubkey";
+            };
+          },
+          {
+            name: "expiresAt";
+            type: {
+              option: "i64";
+            };
+          },
           {
             name: "bump";
             type: "u8";
@@ -205,6 +967,21 @@ export type Escrow = {
     }
   ];
   constants: [
+    {
+      name: "bpsDenominator";
+      type: "u16";
+      value: "10000";
+    },
+    {
+      name: "configSeed";
+      type: "bytes";
+      value: "[99, 111, 110, 102, 105, 103]";
+    },
+    {
+      name: "keeperRewardLamports";
+      type: "u64";
+      value: "100000";
+    },
     {
       name: "seed";
       type: "string";
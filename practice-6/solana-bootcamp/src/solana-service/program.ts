@@ -1,4 +1,11 @@
-import { AnchorProvider, Program, Wallet, web3, BN } from "@coral-xyz/anchor";
+import {
+  AnchorProvider,
+  Program,
+  Wallet,
+  web3,
+  BN,
+  utils,
+} from "@coral-xyz/anchor";
 import NodeWallet from "@coral-xyz/anchor/dist/cjs/nodewallet";
 import { PublicKey } from "@solana/web3.js";
 
@@ -8,6 +15,16 @@ import { config } from "./config";
 import { getAssociatedTokenAddressSync } from "@solana/spl-token";
 import { randomBytes } from "crypto";
 
+// Offset of `allowed_taker` in an offer account: the discriminator, the id,
+// three keys and four amounts come first.
+const ALLOWED_TAKER_OFFSET = 8 + 8 + 32 * 3 + 8 * 4;
+
+// Reads an offer account, no wallet is needed for that.
+export const fetchOffer = (connection: web3.Connection, offer: PublicKey) =>
+  new Program<Escrow>(escrowIdl as Escrow, { connection }).account.offer.fetch(
+    offer
+  );
+
 export class EscrowProgram {
   protected program: Program<Escrow>;
   protected connection: web3.Connection;
@@ -33,19 +50,16 @@ export class EscrowProgram {
     )[0];
   };
 
+  // Offers that want SOL pass `PublicKey.default` as `tokenMintB`.
   async makeOffer(
     tokenMintA: PublicKey,
     tokenMintB: PublicKey,
     tokenAmountA: number,
-    tokenAmountB: number
+    tokenAmountB: number,
+    allowedTaker: PublicKey | null = null
   ) {
     try {
-      const accountInfoA = await this.connection.getAccountInfo(tokenMintA);
-      const accountInfoB = await this.connection.getAccountInfo(tokenMintB);
-      const tokenProgram = accountInfoA!.owner;
-      if (tokenProgram.toBase58() != accountInfoB!.owner.toBase58()) {
-        throw new Error("Token program must be same for both tokens");
-      }
+      const tokenProgram = await this.tokenProgramOf(tokenMintA);
       const offerId = new BN(randomBytes(8));
       const offerAddress = this.createOfferId(offerId);
 
@@ -61,25 +75,30 @@ export class EscrowProgram {
         true,
         tokenProgram
       );
-      const makerTokenAccountB = getAssociatedTokenAddressSync(
-        tokenMintB,
-        this.wallet.publicKey,
-        true,
-        tokenProgram
-      );
+      // An offer that wants SOL has no mint B, the program expects it and its
+      // token program to be left out. Otherwise the two mints may belong to
+      // different token programs.
+      const wantsSol = tokenMintB.equals(PublicKey.default);
       const accounts = {
         maker: this.wallet.publicKey,
         tokenMintA: tokenMintA,
         makerTokenAccountA,
-        tokenMintB: tokenMintB,
-        makerTokenAccountB,
+        tokenMintB: wantsSol ? null : tokenMintB,
         vault,
         offer: offerAddress,
+        tokenProgram,
+        tokenProgramB: wantsSol ? null : await this.tokenProgramOf(tokenMintB),
       };
 
       const txInstruction = await this.program.methods
-        .makeOffer(offerId, new BN(tokenAmountA), new BN(tokenAmountB))
-        .accounts({ ...accounts, tokenProgram: tokenProgram })
+        .makeOffer(
+          offerId,
+          new BN(tokenAmountA),
+          new BN(tokenAmountB),
+          null,
+          allowedTaker
+        )
+        .accountsPartial(accounts)
         .instruction();
 
       const messageV0 = new web3.TransactionMessage({
@@ -104,58 +123,76 @@ export class EscrowProgram {
     }
   }
 
+  // Takes `tokenAAmount` of the offer, paying at most `maxTokenBToPay`. Both
+  // are the amounts the taker was shown, so the take fails instead of paying
+  // more or receiving less if the maker changed the offer in the meantime.
   async takeOffer(
     maker: PublicKey,
     offer: PublicKey,
     tokenMintA: PublicKey,
-    tokenMintB: PublicKey
+    tokenMintB: PublicKey,
+    tokenAAmount: BN,
+    maxTokenBToPay: BN
   ) {
     try {
-      const accountInfoA = await this.connection.getAccountInfo(tokenMintA);
-      const accountInfoB = await this.connection.getAccountInfo(tokenMintB);
-      const tokenProgram = accountInfoA!.owner;
-      if (tokenProgram.toBase58() != accountInfoB!.owner.toBase58()) {
-        throw new Error("Token program must be same for both tokens");
-      }
+      const tokenProgram = await this.tokenProgramOf(tokenMintA);
       const takerTokenAccountA = getAssociatedTokenAddressSync(
         tokenMintA,
         this.wallet.publicKey,
         true,
         tokenProgram
       );
-      const takerTokenAccountB = getAssociatedTokenAddressSync(
-        tokenMintB,
-        this.wallet.publicKey,
-        true,
-        tokenProgram
-      );
-      const makerTokenAccountB = getAssociatedTokenAddressSync(
-        tokenMintB,
-        maker,
-        true,
-        tokenProgram
-      );
-
       const vault = getAssociatedTokenAddressSync(
         tokenMintA,
         offer,
         true,
         tokenProgram
       );
+
+      // An offer that wants SOL has no mint B, so there are no token B
+      // accounts to derive and the program expects them to be left out.
+      let tokenBAccounts;
+      if (tokenMintB.equals(PublicKey.default)) {
+        tokenBAccounts = {
+          tokenMintB: null,
+          tokenProgramB: null,
+          takerTokenAccountB: null,
+          makerTokenAccountB: null,
+          treasuryTokenAccountB: null,
+        };
+      } else {
+        // Mint B may belong to a different token program than mint A.
+        const tokenProgramB = await this.tokenProgramOf(tokenMintB);
+        tokenBAccounts = {
+          tokenMintB,
+          tokenProgramB,
+          takerTokenAccountB: getAssociatedTokenAddressSync(
+            tokenMintB,
+            this.wallet.publicKey,
+            true,
+            tokenProgramB
+          ),
+          makerTokenAccountB: getAssociatedTokenAddressSync(
+            tokenMintB,
+            maker,
+            true,
+            tokenProgramB
+          ),
+        };
+      }
       const accounts = {
         maker,
         offer,
         taker: this.wallet.publicKey,
         takerTokenAccountA,
-        takerTokenAccountB,
         vault,
-        tokenProgram: tokenProgram,
-        makerTokenAccountB,
+        tokenProgram,
+        ...tokenBAccounts,
       };
 
       const txInstruction = await this.program.methods
-        .takeOffer()
-        .accounts({
+        .takeOffer(tokenAAmount, maxTokenBToPay, tokenAAmount)
+        .accountsPartial({
           ...accounts,
         })
         .instruction();
@@ -179,4 +216,24 @@ export class EscrowProgram {
       return null;
     }
   }
+
+  // Owner of `mint`, the Token or the Token-2022 program.
+  private async tokenProgramOf(mint: PublicKey) {
+    return (await this.connection.getAccountInfo(mint))!.owner;
+  }
+
+  // Open offers that only `taker` may take, ours by default.
+  async getOffersForTaker(taker: PublicKey = this.wallet.publicKey) {
+    return await this.program.account.offer.all([
+      {
+        memcmp: {
+          offset: ALLOWED_TAKER_OFFSET,
+          // `Some` tag followed by the key.
+          bytes: utils.bytes.bs58.encode(
+            Buffer.concat([Buffer.from([1]), taker.toBuffer()])
+          ),
+        },
+      },
+    ]);
+  }
 }
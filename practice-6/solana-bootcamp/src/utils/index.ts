@@ -1,3 +1,4 @@
+import { PublicKey } from "@solana/web3.js";
 import { clsx, type ClassValue } from "clsx";
 import { gql } from "graphql-request";
 import { twMerge } from "tailwind-merge";
@@ -24,6 +25,13 @@ export const METADATA = {
   },
 };
 
+// Offers that want SOL instead of a token B use `PublicKey.default` as mint B.
+export const NATIVE_SOL = {
+  address: PublicKey.default.toBase58(),
+  icon: "◎",
+  symbol: "SOL",
+};
+
 export const query = gql`
   {
     offers(first: 100) {